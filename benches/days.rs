@@ -0,0 +1,50 @@
+//! Benchmarks every part of every day against the checked-in inputs.
+//!
+//! Save a baseline with `cargo bench -- --save-baseline <name>` and compare a
+//! later commit against it with `cargo bench -- --baseline <name>`. A single
+//! day can be selected with a filter, e.g. `cargo bench -- day15`.
+//!
+//! Day 16 part 2 needs more than 5 GB of memory, so it's skipped unless
+//! `AOC_BENCH_DAY16_PART2` is set.
+
+use aoc_2022::{Context, DAYS};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::{env, fs, time::Duration};
+
+fn bench(c: &mut Criterion) {
+    for (day, (part1, part2)) in (1..).zip(DAYS) {
+        let path = format!("{}/input{}.txt", env!("CARGO_MANIFEST_DIR"), day);
+        let input =
+            fs::read_to_string(&path).unwrap_or_else(|e| panic!("couldn't read {}: {}", path, e));
+        let context = Context {
+            source: path.clone(),
+            ..Context::default()
+        };
+        let mut group = c.benchmark_group(format!("day{}", day));
+        group.sample_size(10).warm_up_time(Duration::from_secs(1));
+        for (part, solver) in [(1, Some(part1)), (2, part2)] {
+            let solver = match solver {
+                Some(solver) => solver,
+                None => continue,
+            };
+            if (day, part) == (16, 2) && env::var_os("AOC_BENCH_DAY16_PART2").is_none() {
+                continue;
+            }
+            // A solver that fails would be timed failing, so each is checked
+            // once before it's timed.
+            let mut checked = false;
+            group.bench_function(format!("part{}", part), |b| {
+                if !checked {
+                    solver(&input, &context)
+                        .unwrap_or_else(|e| panic!("day {} part {} failed: {}", day, part, e));
+                    checked = true;
+                }
+                b.iter(|| solver(black_box(&input), &context))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
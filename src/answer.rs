@@ -0,0 +1,52 @@
+use std::{
+    fmt::{self, Display},
+    num::TryFromIntError,
+};
+
+/// A puzzle answer. Most parts produce a number, a few produce text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Num(i64),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Num(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Num(n)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Num(n.into())
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Num(n.into())
+    }
+}
+
+impl TryFrom<usize> for Answer {
+    type Error = TryFromIntError;
+
+    fn try_from(n: usize) -> Result<Self, Self::Error> {
+        Ok(Answer::Num(i64::try_from(n)?))
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, bail, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::ErrorKind,
+    path::Path,
+};
+use toml_edit::{table, value, Document, Value};
+
+use crate::Answer;
+
+/// Where known answers are stored when no other path is given.
+pub const DEFAULT_ANSWERS_PATH: &str = "answers.toml";
+
+/// Known-correct answers keyed by day and part, stored as TOML tables like
+/// `[day1]` with `part1 = ...` and `part2 = ...` entries. A day's `skip` entry
+/// lists parts that verify leaves out unless they're asked for, like
+/// `skip = [2]`.
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: BTreeMap<(u8, u8), Answer>,
+    skipped: BTreeSet<(u8, u8)>,
+    /// The file as it was loaded, so saving keeps its comments and layout.
+    document: Document,
+}
+
+/// How a run compares to the stored answer for its part.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    /// The run matched the stored answer.
+    Ok,
+    /// The run produced a different answer than the stored one.
+    Mismatch(Answer),
+    /// The run failed but an answer is stored.
+    Failed(Answer),
+    /// The run produced an answer and none is stored.
+    New,
+    /// The run failed and no answer is stored.
+    Missing,
+}
+
+impl Status {
+    /// Whether a part that used to be solved no longer is.
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Status::Mismatch(_) | Status::Failed(_))
+    }
+}
+
+impl Answers {
+    /// Loads the answers at `path`, treating a missing file as empty.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(e) => bail!("couldn't read {}: {}", path.display(), e),
+        };
+        let document = contents
+            .parse::<Document>()
+            .map_err(|e| anyhow!("couldn't parse {}: {}", path.display(), e))?;
+        let mut answers = BTreeMap::new();
+        let mut skipped = BTreeSet::new();
+        for (day_key, parts) in document.iter() {
+            let day = key_number(day_key, "day")?;
+            let parts = parts
+                .as_table_like()
+                .ok_or_else(|| anyhow!("{} isn't a table", day_key))?;
+            for (part_key, item) in parts.iter() {
+                if part_key == "skip" {
+                    for part in item
+                        .as_array()
+                        .ok_or_else(|| anyhow!("{}.skip isn't an array", day_key))?
+                    {
+                        let part = part
+                            .as_integer()
+                            .and_then(|n| u8::try_from(n).ok())
+                            .filter(|n| (1..=2).contains(n))
+                            .ok_or_else(|| {
+                                anyhow!("{}.skip has a part that isn't 1 or 2", day_key)
+                            })?;
+                        skipped.insert((day, part));
+                    }
+                    continue;
+                }
+                let part = key_number(part_key, "part")?;
+                let answer = match item.as_value() {
+                    Some(Value::Integer(n)) => Answer::Num(*n.value()),
+                    Some(Value::String(s)) => Answer::Text(s.value().clone()),
+                    _ => bail!("{}.{} isn't an integer or string", day_key, part_key),
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+        Ok(Answers {
+            answers,
+            skipped,
+            document,
+        })
+    }
+
+    /// Writes the answers to `path`, keeping the layout and comments of the
+    /// file they were loaded from.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.document.to_string())
+            .map_err(|e| anyhow!("couldn't write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: Answer) {
+        if self.get(day, part) == Some(&answer) {
+            return;
+        }
+        let item = match &answer {
+            Answer::Num(n) => value(*n),
+            Answer::Text(s) => value(s.as_str()),
+        };
+        let key = format!("part{}", part);
+        // Load only accepts tables of parts, so the day's entry is one.
+        if let Some(parts) = self
+            .document
+            .entry(&format!("day{}", day))
+            .or_insert_with(table)
+            .as_table_like_mut()
+        {
+            // Replacing an existing value keeps the comments above its key.
+            match parts.get_mut(&key) {
+                Some(existing) => *existing = item,
+                None => {
+                    parts.insert(&key, item);
+                }
+            }
+        }
+        self.answers.insert((day, part), answer);
+    }
+
+    /// Whether verify leaves out `day`'s `part` unless it's asked for.
+    pub fn is_skipped(&self, day: u8, part: u8) -> bool {
+        self.skipped.contains(&(day, part))
+    }
+
+    /// Compares the result of running `day`'s `part` to the stored answer.
+    pub fn check(&self, day: u8, part: u8, result: &Result<Answer>) -> Status {
+        match (self.get(day, part), result) {
+            (Some(expected), Ok(answer)) if expected == answer => Status::Ok,
+            (Some(expected), Ok(_)) => Status::Mismatch(expected.clone()),
+            (Some(expected), Err(_)) => Status::Failed(expected.clone()),
+            (None, Ok(_)) => Status::New,
+            (None, Err(_)) => Status::Missing,
+        }
+    }
+}
+
+fn key_number(key: &str, prefix: &str) -> Result<u8> {
+    key.strip_prefix(prefix)
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| anyhow!("expected a key like {}N, got {}", prefix, key))
+}
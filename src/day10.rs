@@ -1,23 +1,33 @@
-use anyhow::Result;
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use anyhow::{ensure, Error, Result};
+use std::io::{BufRead, BufReader, Read};
 
-fn main() -> Result<()> {
-    let file = File::open("input10.txt")?;
-    let lines = BufReader::new(file)
+use crate::Answer;
+
+fn parse<R: Read>(reader: R) -> Result<Vec<Option<i32>>> {
+    BufReader::new(reader)
         .lines()
-        .collect::<Result<Vec<String>, std::io::Error>>()?;
+        .map(|l| {
+            l.map_err(Error::new).and_then(|line| {
+                if let Some((_, n_str)) = line.split_once(' ') {
+                    Ok(Some(n_str.parse::<i32>()?))
+                } else {
+                    Ok(None)
+                }
+            })
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let instructions = parse(input.as_bytes())?;
 
     let mut reg = 1;
     let mut cycle = 1;
-    let cycles = vec![20, 60, 100, 140, 180, 220];
+    let cycles = [20, 60, 100, 140, 180, 220];
     let mut sum_strengths = 0;
 
-    for line in &lines {
-        if let Some((_, n_str)) = line.split_once(' ') {
-            let n = n_str.parse::<i32>()?;
+    for instruction in &instructions {
+        if let Some(n) = instruction {
             cycle += 2;
             reg += n;
             if cycles.contains(&cycle) {
@@ -33,14 +43,18 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", sum_strengths);
+    Ok(sum_strengths.into())
+}
 
-    reg = 1;
-    cycle = 0;
+pub fn part2(input: &str) -> Result<Answer> {
+    let instructions = parse(input.as_bytes())?;
+
+    let mut reg: i32 = 1;
+    let mut cycle = 0;
     let mut buf = vec![];
 
-    for line in &lines {
-        if let Some((_, n_str)) = line.split_once(' ') {
+    for instruction in &instructions {
+        if let Some(n) = instruction {
             buf.push(if (reg - cycle % 40).abs() <= 1 {
                 '#'
             } else {
@@ -51,7 +65,6 @@ fn main() -> Result<()> {
             } else {
                 '.'
             });
-            let n = n_str.parse::<i32>()?;
             cycle += 2;
             reg += n;
         } else {
@@ -64,9 +77,10 @@ fn main() -> Result<()> {
         }
     }
 
-    for i in 0..6 {
-        println!("{}", buf[i * 40..i * 40 + 40].iter().collect::<String>());
-    }
-
-    Ok(())
+    ensure!(buf.len() >= 240, "expected 240 cycles, got {}", buf.len());
+    Ok((0..6)
+        .map(|i| buf[i * 40..i * 40 + 40].iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+        .into())
 }
@@ -2,14 +2,22 @@ use anyhow::{anyhow, bail, Result};
 use regex::Regex;
 use std::{
     collections::VecDeque,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
     num::ParseIntError,
 };
 
-fn main() -> Result<()> {
-    let file = File::open("input11.txt")?;
-    let mut lines = BufReader::new(file).lines();
+use crate::Answer;
+
+struct Monkeys {
+    items: Vec<VecDeque<i64>>,
+    operations: Vec<(String, String)>,
+    tests: Vec<i64>,
+    iftrues: Vec<usize>,
+    iffalses: Vec<usize>,
+}
+
+fn parse<R: Read>(reader: R) -> Result<Monkeys> {
+    let mut lines = BufReader::new(reader).lines();
 
     let re_monkey = Regex::new(r"Monkey (\d+):")?;
     let re_items = Regex::new(r"  Starting items: (.+)")?;
@@ -18,7 +26,6 @@ fn main() -> Result<()> {
     let re_iftrue = Regex::new(r"    If true: throw to monkey (\d+)")?;
     let re_iffalse = Regex::new(r"    If false: throw to monkey (\d+)")?;
 
-    let mut monkeys = vec![];
     let mut items = vec![];
     let mut operations = vec![];
     let mut tests = vec![];
@@ -26,17 +33,16 @@ fn main() -> Result<()> {
     let mut iffalses = vec![];
 
     loop {
-        let num = re_monkey
+        re_monkey
             .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
             .ok_or_else(|| anyhow!("no captures"))?[1]
             .parse::<usize>()?;
-        monkeys.push(num);
         let itemlist = re_items
             .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
             .ok_or_else(|| anyhow!("no captures"))?[1]
             .split(", ")
-            .map(|n| n.parse::<i32>())
-            .collect::<Result<Vec<i32>, ParseIntError>>()?;
+            .map(|n| n.parse::<i64>())
+            .collect::<Result<Vec<i64>, ParseIntError>>()?;
         items.push(VecDeque::from(itemlist));
         let operation_line = lines.next().ok_or_else(|| anyhow!("no line"))??;
         let operation_captures = re_operation
@@ -50,7 +56,7 @@ fn main() -> Result<()> {
         let test = re_test
             .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
             .ok_or_else(|| anyhow!("no captures"))?[1]
-            .parse::<i32>()?;
+            .parse::<i64>()?;
         tests.push(test);
         let iftrue = re_iftrue
             .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
@@ -67,16 +73,34 @@ fn main() -> Result<()> {
         }
     }
 
-    let mut inspections = (0..monkeys.len()).map(|_| 0).collect::<Vec<i32>>();
+    Ok(Monkeys {
+        items,
+        operations,
+        tests,
+        iftrues,
+        iffalses,
+    })
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let Monkeys {
+        mut items,
+        operations,
+        tests,
+        iftrues,
+        iffalses,
+    } = parse(input.as_bytes())?;
+
+    let mut inspections = (0..items.len()).map(|_| 0).collect::<Vec<i64>>();
 
     for _ in 0..20 {
-        for m in 0..monkeys.len() {
+        for m in 0..items.len() {
             while let Some(mut i) = items[m].pop_front() {
                 inspections[m] += 1;
                 let operand2 = if operations[m].1 == "old" {
                     i
                 } else {
-                    operations[m].1.parse::<i32>()?
+                    operations[m].1.parse::<i64>()?
                 };
                 i = match operations[m].0.as_str() {
                     "*" => i * operand2,
@@ -95,65 +119,23 @@ fn main() -> Result<()> {
     }
     inspections.sort();
     inspections.reverse();
-    println!("{:?}", inspections.iter().take(2).product::<i32>());
-
-    let file = File::open("input11.txt")?;
-    let mut lines = BufReader::new(file).lines();
-
-    let mut monkeys = vec![];
-    let mut items = vec![];
-    let mut operations = vec![];
-    let mut tests = vec![];
-    let mut iftrues = vec![];
-    let mut iffalses = vec![];
+    Ok(inspections.iter().take(2).product::<i64>().into())
+}
 
-    loop {
-        let num = re_monkey
-            .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
-            .ok_or_else(|| anyhow!("no captures"))?[1]
-            .parse::<usize>()?;
-        monkeys.push(num);
-        let itemlist = re_items
-            .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
-            .ok_or_else(|| anyhow!("no captures"))?[1]
-            .split(", ")
-            .map(|n| n.parse::<i64>())
-            .collect::<Result<Vec<i64>, ParseIntError>>()?;
-        items.push(VecDeque::from(itemlist));
-        let operation_line = lines.next().ok_or_else(|| anyhow!("no line"))??;
-        let operation_captures = re_operation
-            .captures(&operation_line)
-            .ok_or_else(|| anyhow!("no captures"))?;
-        let operation = (
-            operation_captures[1].to_string(),
-            operation_captures[2].to_string(),
-        );
-        operations.push(operation);
-        let test = re_test
-            .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
-            .ok_or_else(|| anyhow!("no captures"))?[1]
-            .parse::<i64>()?;
-        tests.push(test);
-        let iftrue = re_iftrue
-            .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
-            .ok_or_else(|| anyhow!("no captures"))?[1]
-            .parse::<usize>()?;
-        iftrues.push(iftrue);
-        let iffalse = re_iffalse
-            .captures(&lines.next().ok_or_else(|| anyhow!("no line"))??)
-            .ok_or_else(|| anyhow!("no captures"))?[1]
-            .parse::<usize>()?;
-        iffalses.push(iffalse);
-        if lines.next().is_none() {
-            break;
-        }
-    }
+pub fn part2(input: &str) -> Result<Answer> {
+    let Monkeys {
+        mut items,
+        operations,
+        tests,
+        iftrues,
+        iffalses,
+    } = parse(input.as_bytes())?;
 
-    let mut inspections = (0..monkeys.len()).map(|_| 0).collect::<Vec<i64>>();
+    let mut inspections = (0..items.len()).map(|_| 0).collect::<Vec<i64>>();
     let modulus = tests.iter().product::<i64>();
 
     for _ in 0..10000 {
-        for m in 0..monkeys.len() {
+        for m in 0..items.len() {
             while let Some(mut i) = items[m].pop_front() {
                 inspections[m] += 1;
                 let operand2 = if operations[m].1 == "old" {
@@ -179,7 +161,5 @@ fn main() -> Result<()> {
 
     inspections.sort();
     inspections.reverse();
-    println!("{:?}", inspections.iter().take(2).product::<i64>());
-
-    Ok(())
+    Ok(inspections.iter().take(2).product::<i64>().into())
 }
@@ -1,13 +1,20 @@
 use anyhow::{anyhow, Result};
 use std::{
     collections::VecDeque,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
 };
 
-fn main() -> Result<()> {
-    let file = File::open("input12.txt")?;
-    let grid = BufReader::new(file)
+use crate::Answer;
+
+struct Heightmap {
+    grid: Vec<String>,
+    start_pos: (usize, usize),
+    end_pos: (usize, usize),
+    heights: Vec<Vec<i32>>,
+}
+
+fn parse<R: Read>(reader: R) -> Result<Heightmap> {
+    let grid = BufReader::new(reader)
         .lines()
         .collect::<Result<Vec<String>, std::io::Error>>()?;
 
@@ -36,13 +43,25 @@ fn main() -> Result<()> {
         })
         .collect::<Vec<Vec<i32>>>();
 
+    Ok(Heightmap {
+        grid,
+        start_pos,
+        end_pos,
+        heights,
+    })
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let Heightmap {
+        grid,
+        start_pos,
+        end_pos,
+        heights,
+    } = parse(input.as_bytes())?;
+
     let mut dists = grid
         .iter()
-        .map(|row| {
-            (0..row.len())
-                .map(|_| usize::max_value())
-                .collect::<Vec<usize>>()
-        })
+        .map(|row| (0..row.len()).map(|_| usize::MAX).collect::<Vec<usize>>())
         .collect::<Vec<Vec<usize>>>();
 
     let mut queue: VecDeque<(usize, usize)> = VecDeque::from(vec![start_pos]);
@@ -53,7 +72,7 @@ fn main() -> Result<()> {
     while let Some((r, c)) = queue.pop_front() {
         if (r, c) == start_pos {
             dists[r][c] = 0;
-        } else if dists[r][c] != usize::max_value() {
+        } else if dists[r][c] != usize::MAX {
             continue;
         }
         let mut coords_to_check: Vec<(usize, usize)> = vec![];
@@ -72,7 +91,7 @@ fn main() -> Result<()> {
         let h = heights[r][c];
         for (r_, c_) in coords_to_check {
             let d_ = dists[r_][c_];
-            if d_ == usize::max_value() {
+            if d_ == usize::MAX {
                 if heights[r_][c_] - h <= 1 {
                     queue.push_back((r_, c_));
                 }
@@ -83,7 +102,16 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", dists[end_pos.0][end_pos.1]);
+    Ok(Answer::try_from(dists[end_pos.0][end_pos.1])?)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let Heightmap {
+        grid,
+        end_pos,
+        heights,
+        ..
+    } = parse(input.as_bytes())?;
 
     let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
 
@@ -98,15 +126,18 @@ fn main() -> Result<()> {
                         queue.push_back((i, j));
                         0
                     } else {
-                        usize::max_value()
+                        usize::MAX
                     }
                 })
                 .collect::<Vec<usize>>()
         })
         .collect::<Vec<Vec<usize>>>();
 
+    let height = grid.len();
+    let width = grid[0].len();
+
     while let Some((r, c)) = queue.pop_front() {
-        if dists[r][c] != usize::max_value() && dists[r][c] != 0 {
+        if dists[r][c] != usize::MAX && dists[r][c] != 0 {
             continue;
         }
         let mut coords_to_check: Vec<(usize, usize)> = vec![];
@@ -125,7 +156,7 @@ fn main() -> Result<()> {
         let h = heights[r][c];
         for (r_, c_) in coords_to_check {
             let d_ = dists[r_][c_];
-            if d_ == usize::max_value() {
+            if d_ == usize::MAX {
                 if heights[r_][c_] - h <= 1 {
                     queue.push_back((r_, c_));
                 }
@@ -136,7 +167,5 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", dists[end_pos.0][end_pos.1]);
-
-    Ok(())
+    Ok(Answer::try_from(dists[end_pos.0][end_pos.1])?)
 }
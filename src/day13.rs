@@ -1,10 +1,11 @@
 use anyhow::{anyhow, bail, Error, Result};
 use std::{
     cmp::Ordering,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 #[derive(Debug, Eq)]
 enum List {
     Num(u32),
@@ -49,7 +50,7 @@ impl PartialOrd for List {
     }
 }
 
-fn parse(input: &mut dyn Iterator<Item = char>) -> Result<List> {
+fn parse_list(input: &mut dyn Iterator<Item = char>) -> Result<List> {
     let mut n = None;
     let mut v = vec![];
     while let Some(c) = input.next() {
@@ -70,7 +71,7 @@ fn parse(input: &mut dyn Iterator<Item = char>) -> Result<List> {
             }
             return Ok(List::List(v));
         } else if c == '[' {
-            n = Some(parse(input)?);
+            n = Some(parse_list(input)?);
         } else {
             bail!("unhandled value of c: {}", c);
         }
@@ -78,53 +79,42 @@ fn parse(input: &mut dyn Iterator<Item = char>) -> Result<List> {
     bail!("no more characters")
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input13.txt")?;
-    let mut lines = BufReader::new(file).lines();
-
-    let mut sum_indices = 0;
+fn parse<R: Read>(reader: R) -> Result<Vec<List>> {
+    BufReader::new(reader)
+        .lines()
+        .filter(|l| !matches!(l, Ok(l) if l.is_empty()))
+        .map(|l| {
+            l.map_err(Error::new).and_then(|l| {
+                let mut chars = l.chars();
+                if chars.next() != Some('[') {
+                    bail!("no open bracket in packet: {}", l)
+                }
+                parse_list(&mut chars)
+            })
+        })
+        .collect()
+}
 
-    for i in 1.. {
-        let v1 = lines.next().ok_or_else(|| anyhow!("no v1"))??;
-        let v2 = lines.next().ok_or_else(|| anyhow!("no v2"))??;
+pub fn part1(input: &str) -> Result<Answer> {
+    let packets = parse(input.as_bytes())?;
 
-        let mut chars1 = v1.chars();
-        if let Some(c) = chars1.next() {
-            if c != '[' {
-                bail!("no open bracked in pair 1")
-            }
-        }
+    let mut sum_indices = 0;
 
-        let mut chars2 = v2.chars();
-        if let Some(c) = chars2.next() {
-            if c != '[' {
-                bail!("no open bracked in pair 2")
+    for (i, pair) in packets.chunks(2).enumerate() {
+        if let [parsed1, parsed2] = pair {
+            if parsed1.cmp(parsed2) == Ordering::Less {
+                sum_indices += i + 1;
             }
-        }
-
-        let parsed1 = parse(&mut chars1)?;
-        let parsed2 = parse(&mut chars2)?;
-
-        if parsed1.cmp(&parsed2) == Ordering::Less {
-            sum_indices += i;
-        }
-
-        if lines.next().is_none() {
-            break;
+        } else {
+            bail!("no pair for packet {}", 2 * i + 1)
         }
     }
 
-    println!("{}", sum_indices);
+    Ok(Answer::try_from(sum_indices)?)
+}
 
-    let file = File::open("input13.txt")?;
-    let mut lists = BufReader::new(file)
-        .lines()
-        .filter(|l| !l.as_ref().unwrap().is_empty())
-        .map(|l| {
-            l.map_err(Error::new)
-                .and_then(|l| parse(&mut l.chars().skip(1)))
-        })
-        .collect::<Result<Vec<List>>>()?;
+pub fn part2(input: &str) -> Result<Answer> {
+    let mut lists = parse(input.as_bytes())?;
 
     lists.push(List::List(vec![List::List(vec![List::Num(2)])]));
     lists.push(List::List(vec![List::List(vec![List::Num(6)])]));
@@ -140,7 +130,5 @@ fn main() -> Result<()> {
         .position(|l| *l == List::List(vec![List::List(vec![List::Num(6)])]))
         .ok_or_else(|| anyhow!("couldn't find divider 2"))?;
 
-    println!("{}", (i1 + 1) * (i2 + 1));
-
-    Ok(())
+    Ok(Answer::try_from((i1 + 1) * (i2 + 1))?)
 }
@@ -1,12 +1,10 @@
 use anyhow::{anyhow, bail, Error, Result};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-fn parse() -> Result<Vec<Vec<(usize, usize)>>> {
-    let file = File::open("input14.txt")?;
-    BufReader::new(file)
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
+
+fn parse<R: Read>(reader: R) -> Result<Vec<Vec<(usize, usize)>>> {
+    BufReader::new(reader)
         .lines()
         .map(|l| {
             l.map_err(Error::new).and_then(|l| {
@@ -27,9 +25,7 @@ fn parse() -> Result<Vec<Vec<(usize, usize)>>> {
         .collect::<Result<Vec<Vec<(usize, usize)>>>>()
 }
 
-fn main() -> Result<()> {
-    let paths = parse()?;
-
+fn bounds(paths: &[Vec<(usize, usize)>]) -> Result<(usize, usize, usize)> {
     let xmax = paths
         .iter()
         .map(|path| path.iter().map(|coord| coord.0).max())
@@ -51,6 +47,13 @@ fn main() -> Result<()> {
         .ok_or_else(|| anyhow!("no outer max"))?
         .ok_or_else(|| anyhow!("no inner max"))?;
 
+    Ok((xmin, xmax, ymax))
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let paths = parse(input.as_bytes())?;
+    let (xmin, xmax, ymax) = bounds(&paths)?;
+
     let mut grid = (0..ymax + 1)
         .map(|_| (xmin..xmax + 1).map(|_| '.').collect())
         .collect::<Vec<Vec<char>>>();
@@ -110,7 +113,12 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", n_grains.ok_or_else(|| anyhow!("n_grains not set"))?);
+    Ok(n_grains.ok_or_else(|| anyhow!("n_grains not set"))?.into())
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let paths = parse(input.as_bytes())?;
+    let (_, _, ymax) = bounds(&paths)?;
 
     let ymax = ymax + 2;
     let xmin = 500 - ymax;
@@ -174,7 +182,5 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", n_grains.ok_or_else(|| anyhow!("n_grains not set"))?);
-
-    Ok(())
+    Ok(n_grains.ok_or_else(|| anyhow!("n_grains not set"))?.into())
 }
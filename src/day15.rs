@@ -1,9 +1,8 @@
 use anyhow::{anyhow, bail, Error, Result};
 use regex::Regex;
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Read},
-};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
 
 fn parse<R: Read>(reader: R) -> Result<Vec<(i64, i64, i64)>> {
     let re =
@@ -25,28 +24,36 @@ fn parse<R: Read>(reader: R) -> Result<Vec<(i64, i64, i64)>> {
         .collect::<Result<Vec<(i64, i64, i64)>>>()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input15.txt")?;
-    let coords = parse(file)?;
+pub fn part1(input: &str) -> Result<Answer> {
+    excluded_in_row(input, 2000000)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    tuning_frequency(input, 4000000)
+}
 
-    let sum_merged_lengths = get_ranges(2000000, &coords)?
+/// Counts the positions in row `y` where a beacon cannot be present.
+pub fn excluded_in_row(input: &str, y: i64) -> Result<Answer> {
+    let coords = parse(input.as_bytes())?;
+
+    let sum_merged_lengths = get_ranges(y, &coords)?
         .iter()
         .map(|(x1, x2)| x2 - x1)
         .sum::<i64>();
 
-    println!("{}", sum_merged_lengths);
+    Ok(sum_merged_lengths.into())
+}
 
-    const B: i64 = 4000000;
+/// Finds the only uncovered position with both coordinates in `0..=bound`.
+pub fn tuning_frequency(input: &str, bound: i64) -> Result<Answer> {
+    let coords = parse(input.as_bytes())?;
 
-    for y in 0..B + 1 {
-        if y % 1000000 == 0 {
-            println!("y={}", y);
-        }
+    for y in 0..bound + 1 {
         let ranges = get_ranges(y, &coords)?;
         let filtered_ranges = ranges
             .iter()
             .filter_map(|(x1, x2)| {
-                if *x1 <= B || *x2 >= 0 {
+                if *x1 <= bound && *x2 >= 0 {
                     Some((*x1, *x2))
                 } else {
                     None
@@ -58,17 +65,17 @@ fn main() -> Result<()> {
                 if x21 - x12 != 2 {
                     bail!("unexpected range values: {}, {}", x12, x21);
                 }
-                println!("{}", (x12 + 1) * B + y);
+                return Ok(((x12 + 1) * 4000000 + y).into());
             } else {
                 bail!("unexpected vec length {}", filtered_ranges.len());
             }
         }
     }
 
-    Ok(())
+    bail!("couldn't find a gap in any row")
 }
 
-fn get_ranges(y: i64, coords: &Vec<(i64, i64, i64)>) -> Result<Vec<(i64, i64)>> {
+fn get_ranges(y: i64, coords: &[(i64, i64, i64)]) -> Result<Vec<(i64, i64)>> {
     let mut ranges: Vec<(i64, i64)> = vec![];
 
     for (sx, sy, db) in coords {
@@ -3,10 +3,11 @@ use itertools::Itertools;
 use regex::Regex;
 use std::{
     collections::HashMap,
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 fn parse<R: Read>(reader: R) -> Result<Vec<(String, i64, Vec<String>)>> {
     let re =
         Regex::new(r"Valve ([A-Z]+) has flow rate=(\d+); tunnels? leads? to valves? ([A-Z, ]+)")?;
@@ -214,9 +215,8 @@ fn solve2<'a>(
     Ok(max)
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input16.txt")?;
-    let valves = parse(file)?;
+pub fn part1(input: &str) -> Result<Answer> {
+    let valves = parse(input.as_bytes())?;
     let flattened = flatten(&valves)?;
 
     let flattened_map = flattened
@@ -235,6 +235,20 @@ fn main() -> Result<()> {
         &mut HashMap::new(),
     )?;
 
+    Ok(result.0.into())
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let valves = parse(input.as_bytes())?;
+    let flattened = flatten(&valves)?;
+
+    let flattened_map = flattened
+        .iter()
+        .map(|(cur, flow, out)| (*cur, (flow, out)))
+        .collect::<HashMap<&String, (&i64, &Vec<(&String, i64)>)>>();
+
+    let aa = "AA".to_string();
+
     let mut all_pairs: HashMap<(&String, &String), i64> = HashMap::new();
 
     for (l1, (_flow, outs)) in &flattened_map {
@@ -279,8 +293,6 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{} {:?}", result.0, result.1);
-
     let result2 = solve2(
         &all_dists,
         &aa,
@@ -292,7 +304,5 @@ fn main() -> Result<()> {
         &mut HashMap::new(),
     )?;
 
-    println!("{}", result2);
-
-    Ok(())
+    Ok(result2.into())
 }
@@ -1,5 +1,7 @@
 use anyhow::{ensure, Result};
-use std::{fs::File, io::Read};
+use std::{collections::HashMap, io::Read};
+
+use crate::Answer;
 
 #[derive(Debug)]
 enum Dir {
@@ -20,18 +22,19 @@ fn parse<R: Read>(mut reader: R) -> Result<Vec<Dir>> {
         .collect())
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input17.txt")?;
-    let gusts = parse(file)?;
+pub fn part1(input: &str) -> Result<Answer> {
+    let gusts = parse(input.as_bytes())?;
 
-    println!("{}", sim(&gusts, 2022)?);
+    Ok(Answer::try_from(sim(&gusts, 2022)?)?)
+}
 
-    println!("{}", sim(&gusts, 1_000_000_000_000)?);
+pub fn part2(input: &str) -> Result<Answer> {
+    let gusts = parse(input.as_bytes())?;
 
-    Ok(())
+    Ok(Answer::try_from(sim(&gusts, 1_000_000_000_000)?)?)
 }
 
-fn _print(board: &Vec<[bool; 7]>, mut topn: usize) {
+fn _print(board: &[[bool; 7]], mut topn: usize) {
     if topn == 0 || topn > board.len() {
         topn = board.len();
     }
@@ -49,7 +52,7 @@ fn _print(board: &Vec<[bool; 7]>, mut topn: usize) {
 }
 
 fn is_blocked(
-    board: &Vec<[bool; 7]>,
+    board: &[[bool; 7]],
     shape: &(Vec<(usize, usize)>, usize),
     x: usize,
     y: usize,
@@ -65,34 +68,13 @@ fn is_blocked(
     false
 }
 
-fn sim(gusts: &Vec<Dir>, n: usize) -> Result<usize> {
-    let (len, cycle) = sim_(gusts, 0, 0, n)?;
-    if let Some(Cycle {
-        tshape,
-        clen,
-        cshape,
-        ishape,
-        igust,
-    }) = cycle
-    {
-        let ncycles = (n - tshape) / cshape;
-        let rshape = n - ncycles * cshape - tshape;
-        let (lenr, cycler) = sim_(gusts, ishape, igust, rshape)?;
-        ensure!(cycler.is_none(), "found trailing cycle");
-        return Ok(len + ncycles * clen + lenr);
-    }
-    Ok(len)
-}
+/// Number of rows from the top of the tower that are compared when looking for a cycle.
+const SURFACE: usize = 32;
 
-struct Cycle {
-    tshape: usize,
-    clen: usize,
-    cshape: usize,
-    ishape: usize,
-    igust: usize,
-}
+/// The next shape, the next gust and the top of the tower.
+type State = (usize, usize, Vec<[bool; 7]>);
 
-fn sim_(gusts: &Vec<Dir>, ishape: usize, igust: usize, n: usize) -> Result<(usize, Option<Cycle>)> {
+fn sim(gusts: &[Dir], n: usize) -> Result<usize> {
     let shapes: Vec<(Vec<(usize, usize)>, usize)> = vec![
         (vec![(0, 0), (1, 0), (2, 0), (3, 0)], 1),
         (vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)], 3),
@@ -102,77 +84,74 @@ fn sim_(gusts: &Vec<Dir>, ishape: usize, igust: usize, n: usize) -> Result<(usiz
     ];
 
     let mut board: Vec<[bool; 7]> = Vec::new();
+    let mut igust = 0;
+    let mut seen: HashMap<State, (usize, usize)> = HashMap::new();
+    let mut skipped = 0;
+    let mut tshape = 0;
+
+    while tshape < n {
+        let ishape = tshape % shapes.len();
+        drop_shape(&mut board, &shapes[ishape], gusts, &mut igust)?;
+        tshape += 1;
+
+        if skipped == 0 && board.len() >= SURFACE {
+            let key = (ishape, igust, board[board.len() - SURFACE..].to_vec());
+            if let Some((tshapel, lenl)) = seen.insert(key, (tshape, board.len())) {
+                let cshape = tshape - tshapel;
+                let ncycles = (n - tshape) / cshape;
+                skipped = ncycles * (board.len() - lenl);
+                tshape += ncycles * cshape;
+            }
+        }
+    }
 
-    let mut igust = igust;
-    let mut last: Option<(usize, usize, usize, usize)> = None;
-
-    for (tshape, (ishape, shape)) in shapes
-        .iter()
-        .enumerate()
-        .cycle()
-        .enumerate()
-        .skip(ishape)
-        .take(n)
-    {
-        let mut x = 2;
-        let mut y = board.len() + 3;
-
-        loop {
-            let gust = &gusts[igust];
-            igust = (igust + 1) % gusts.len();
-
-            let x_ = match gust {
-                Dir::L => {
-                    if x > 0 {
-                        x - 1
-                    } else {
-                        0
-                    }
-                }
-                Dir::R => x + 1,
-            };
+    Ok(board.len() + skipped)
+}
 
-            if !is_blocked(&board, shape, x_, y) {
-                x = x_;
-            }
-            if y > 0 && !is_blocked(&board, shape, x, y - 1) {
-                y -= 1;
-            } else {
-                let maxy = y + shape.1 - 1;
-                if maxy >= board.len() {
-                    board.append(&mut (board.len()..maxy + 1).map(|_| [false; 7]).collect());
-                }
-                for (dx, dy) in &shape.0 {
-                    ensure!(
-                        !board[y + dy][x + dx],
-                        "setting board pos that was already set: {}, {}",
-                        x + dx,
-                        y + dy,
-                    );
-                    board[y + dy][x + dx] = true;
+fn drop_shape(
+    board: &mut Vec<[bool; 7]>,
+    shape: &(Vec<(usize, usize)>, usize),
+    gusts: &[Dir],
+    igust: &mut usize,
+) -> Result<()> {
+    let mut x = 2;
+    let mut y = board.len() + 3;
+
+    loop {
+        let gust = &gusts[*igust];
+        *igust = (*igust + 1) % gusts.len();
+
+        let x_ = match gust {
+            Dir::L => {
+                if x > 0 {
+                    x - 1
+                } else {
+                    0
                 }
-                break;
             }
-        }
+            Dir::R => x + 1,
+        };
 
-        if board[board.len() - 1].iter().all(|x| *x) {
-            if let Some((tshapel, lenl, ishapel, igustl)) = last {
-                if ishapel == ishape && igustl == igust {
-                    return Ok((
-                        board.len(),
-                        Some(Cycle {
-                            tshape: tshape + 1,
-                            clen: board.len() - lenl,
-                            cshape: tshape - tshapel,
-                            ishape: ishape + 1,
-                            igust,
-                        }),
-                    ));
-                }
-            } else {
-                last = Some((tshape, board.len(), ishape, igust));
+        if !is_blocked(board, shape, x_, y) {
+            x = x_;
+        }
+        if y > 0 && !is_blocked(board, shape, x, y - 1) {
+            y -= 1;
+        } else {
+            let maxy = y + shape.1 - 1;
+            if maxy >= board.len() {
+                board.append(&mut (board.len()..maxy + 1).map(|_| [false; 7]).collect());
+            }
+            for (dx, dy) in &shape.0 {
+                ensure!(
+                    !board[y + dy][x + dx],
+                    "setting board pos that was already set: {}, {}",
+                    x + dx,
+                    y + dy,
+                );
+                board[y + dy][x + dx] = true;
             }
+            return Ok(());
         }
     }
-    Ok((board.len(), None))
 }
@@ -2,10 +2,11 @@ use anyhow::{anyhow, ensure, Error, Result};
 
 use std::{
     collections::{HashSet, VecDeque},
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 fn parse<R: Read>(reader: R) -> Result<Vec<(i64, i64, i64)>> {
     BufReader::new(reader)
         .lines()
@@ -22,25 +23,13 @@ fn parse<R: Read>(reader: R) -> Result<Vec<(i64, i64, i64)>> {
         .collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input18.txt")?;
-
-    let cubes = parse(file)?;
-
-    let surface_area = part1(&cubes)?;
-    println!("{}", surface_area);
-
-    let outside_area = part2(&cubes)?;
-    println!("{}", outside_area);
+pub fn part1(input: &str) -> Result<Answer> {
+    let cubes = parse(input.as_bytes())?;
 
-    Ok(())
-}
-
-fn part1(cubes: &[(i64, i64, i64)]) -> Result<i64> {
     let set: HashSet<&(i64, i64, i64)> = cubes.iter().collect();
 
     let mut total_area = 0;
-    for (x, y, z) in cubes {
+    for (x, y, z) in &cubes {
         for (dx, dy, dz) in [
             (-1, 0, 0),
             (1, 0, 0),
@@ -54,10 +43,12 @@ fn part1(cubes: &[(i64, i64, i64)]) -> Result<i64> {
             }
         }
     }
-    Ok(total_area)
+    Ok(total_area.into())
 }
 
-fn part2(cubes: &[(i64, i64, i64)]) -> Result<i64> {
+pub fn part2(input: &str) -> Result<Answer> {
+    let cubes = parse(input.as_bytes())?;
+
     let cubes_set: HashSet<&(i64, i64, i64)> = cubes.iter().collect();
     let maxx = cubes
         .iter()
@@ -101,7 +92,7 @@ fn part2(cubes: &[(i64, i64, i64)]) -> Result<i64> {
         }
     }
     let mut outside_area = 0;
-    for (x, y, z) in cubes {
+    for (x, y, z) in &cubes {
         for (dx, dy, dz) in [
             (-1, 0, 0),
             (1, 0, 0),
@@ -128,5 +119,5 @@ fn part2(cubes: &[(i64, i64, i64)]) -> Result<i64> {
             }
         }
     }
-    Ok(outside_area)
+    Ok(outside_area.into())
 }
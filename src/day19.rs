@@ -2,10 +2,11 @@ use anyhow::{anyhow, Error, Result};
 use regex::Regex;
 use std::{
     collections::HashMap,
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 #[derive(Debug)]
 struct Blueprint {
     ore: i64,
@@ -71,19 +72,10 @@ fn parse<R: Read>(reader: R) -> Result<Vec<Blueprint>> {
         .collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input19.txt")?;
-
-    let blueprints = parse(file)?;
-
-    println!("{}", part1(&blueprints)?);
-    println!("{}", part2(&blueprints)?);
+pub fn part1(input: &str) -> Result<Answer> {
+    let blueprints = parse(input.as_bytes())?;
 
-    Ok(())
-}
-
-fn part1(blueprints: &[Blueprint]) -> Result<i64> {
-    blueprints
+    Ok(blueprints
         .iter()
         .enumerate()
         .map(|(i, b)| {
@@ -107,11 +99,14 @@ fn part1(blueprints: &[Blueprint]) -> Result<i64> {
             )
             .and_then(|q| Ok((TryInto::<i64>::try_into(i)? + 1) * q))
         })
-        .sum()
+        .sum::<Result<i64>>()?
+        .into())
 }
 
-fn part2(blueprints: &[Blueprint]) -> Result<i64> {
-    blueprints
+pub fn part2(input: &str) -> Result<Answer> {
+    let blueprints = parse(input.as_bytes())?;
+
+    Ok(blueprints
         .iter()
         .take(3)
         .map(|b| {
@@ -133,7 +128,8 @@ fn part2(blueprints: &[Blueprint]) -> Result<i64> {
                 CouldHaveProduced::none(),
             )
         })
-        .product()
+        .product::<Result<i64>>()?
+        .into())
 }
 
 fn solve(
@@ -1,170 +1,331 @@
-use anyhow::{anyhow, Error, Result};
-use futures::stream::TryStreamExt;
-use mongodb::{
-    bson::{doc, Document},
-    options::ClientOptions,
-    Client,
-};
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use itertools::Itertools;
+use serde::Deserialize;
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+    cmp::Reverse,
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::Path,
 };
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let file = File::open("input2.txt")?;
-    let lines = BufReader::new(file).lines();
-
-    let client_options = ClientOptions::parse("mongodb://localhost").await?;
-    let client = Client::with_options(client_options)?;
-
-    let database = client.database("aoc2022");
-
-    let input2 = database.collection::<Document>("input2");
-
-    input2.drop(None).await?;
-
-    input2
-        .insert_many(
-            lines
-                .map(|l| {
-                    l.map_err(Error::new)
-                        .and_then(|c| {
-                            c.split_once(' ')
-                                .map(|(a, b)| (a.to_owned(), b.to_owned()))
-                                .ok_or_else(|| anyhow!("Couldn't parse line"))
-                        })
-                        .map(|(opp, self_)| {
-                            doc! { "self": self_, "opp": opp }
-                        })
-                })
-                .collect::<Result<Vec<Document>>>()?,
-            None,
-        )
-        .await?;
-
-    let mut cursor = input2
-        .aggregate(
-            [
-                doc! {
-                    "$project": doc! {
-                        "score": doc! {
-                            "$add": [
-                                doc! {
-                                    "$indexOfArray": [["X", "Y", "Z"], "$self"]
-                                },
-                                1,
-                                doc! {
-                                    "$arrayElemAt": [
-                                        [3, 6, 0],
-                                        doc! {
-                                            "$mod": [
-                                                doc! {
-                                                    "$add": [
-                                                        doc! {
-                                                            "$subtract": [
-                                                                doc! {
-                                                                    "$indexOfArray": [
-                                                                        ["X", "Y", "Z"],
-                                                                        "$self",
-                                                                    ],
-                                                                },
-                                                                doc! {
-                                                                    "$indexOfArray": [
-                                                                        ["A", "B", "C"],
-                                                                        "$opp",
-                                                                    ],
-                                                                },
-                                                            ],
-                                                        },
-                                                        3,
-                                                    ],
-                                                },
-                                                3,
-                                            ],
-                                        },
-                                    ],
-                                },
-                            ],
-                        },
-                    }
-                },
-                doc! {
-                    "$group": doc! {
-                        "_id": 0,
-                        "total_score": doc! {
-                            "$sum": "$score",
-                        },
-                    },
-                },
-            ],
-            None,
-        )
-        .await?;
-    while let Some(result) = cursor.try_next().await? {
-        println!("{}", result);
-    }
-
-    let mut cursor = input2
-        .aggregate(
-            [
-                doc! {
-                    "$project": doc! {
-                        "score": doc! {
-                            "$add": [
-                                doc! {
-                                    "$arrayElemAt": [
-                                        [0, 3, 6],
-                                        doc! {
-                                            "$indexOfArray": [["X", "Y", "Z"], "$self"]
-                                        },
-                                    ],
-                                },
-                                doc! {
-                                    "$arrayElemAt": [
-                                        [3, 1, 2],
-                                        doc! {
-                                            "$mod": [
-                                                doc! {
-                                                    "$add": [
-                                                        doc! {
-                                                            "$indexOfArray": [
-                                                                ["X", "Y", "Z"],
-                                                                "$self",
-                                                            ],
-                                                        },
-                                                        doc! {
-                                                            "$indexOfArray": [
-                                                                ["A", "B", "C"],
-                                                                "$opp",
-                                                            ],
-                                                        },
-                                                    ],
-                                                },
-                                                3,
-                                            ],
-                                        },
-                                    ],
-                                },
-                            ],
-                        },
-                    }
-                },
-                doc! {
-                    "$group": doc! {
-                        "_id": 0,
-                        "total_score": doc! {
-                            "$sum": "$score",
-                        },
-                    },
-                },
-            ],
-            None,
-        )
-        .await?;
-    while let Some(result) = cursor.try_next().await? {
-        println!("{}", result);
-    }
-
-    Ok(())
+use crate::Answer;
+
+/// The most shapes [`Rules::mappings`] tries every mapping of.
+pub const MAX_MAPPED_SHAPES: usize = 9;
+
+/// A hand game with an odd number of shapes in which every shape beats half
+/// of the others, such as rock-paper-scissors, read from a TOML rules file
+/// like those in `rules/`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Rules {
+    pub shapes: Vec<Shape>,
+    pub outcomes: Outcomes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Shape {
+    pub name: String,
+    /// The opponent's symbol for this shape.
+    pub opponent: String,
+    /// The strategy's symbol for this shape, when it's read as a shape.
+    pub player: String,
+    pub score: i64,
+    /// The names of the shapes this one beats.
+    pub beats: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Outcomes {
+    pub lose: OutcomeRule,
+    pub draw: OutcomeRule,
+    pub win: OutcomeRule,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct OutcomeRule {
+    /// The strategy's symbol for this outcome, when it's read as an outcome.
+    pub player: String,
+    pub score: i64,
+}
+
+/// A round's result for the player, in the order outcomes are indexed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::rock_paper_scissors()
+    }
+}
+
+impl Rules {
+    pub fn rock_paper_scissors() -> Self {
+        Rules::parse(include_str!("../rules/rock-paper-scissors.toml"))
+            .expect("the built-in rules are valid")
+    }
+
+    /// Reads and checks a rules file.
+    pub fn parse(rules: &str) -> Result<Self> {
+        let rules: Rules = toml_edit::de::from_str(rules)?;
+        rules.check()?;
+        Ok(rules)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Rules::parse(&fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("invalid rules in {}: {}", path.display(), e))
+    }
+
+    fn check(&self) -> Result<()> {
+        let n = self.shapes.len();
+        ensure!(
+            n >= 3 && n % 2 == 1,
+            "a game needs an odd number of shapes, at least 3, not {}",
+            n
+        );
+        for (i, shape) in self.shapes.iter().enumerate() {
+            for beaten in &shape.beats {
+                ensure!(
+                    self.shapes.iter().any(|s| s.name == *beaten),
+                    "{} beats unknown shape {}",
+                    shape.name,
+                    beaten
+                );
+                ensure!(beaten != &shape.name, "{} can't beat itself", shape.name);
+            }
+            for (j, other) in self.shapes.iter().enumerate().take(i) {
+                ensure!(
+                    shape.name != other.name
+                        && shape.opponent != other.opponent
+                        && shape.player != other.player,
+                    "{} and {} share a name or symbol",
+                    other.name,
+                    shape.name
+                );
+                ensure!(
+                    self.beats(i, j) != self.beats(j, i),
+                    "exactly one of {} and {} must beat the other",
+                    other.name,
+                    shape.name
+                );
+            }
+            ensure!(
+                shape.beats.len() == n / 2,
+                "{} beats {} shapes instead of half the others, {}",
+                shape.name,
+                shape.beats.len(),
+                n / 2
+            );
+        }
+        let players = [
+            &self.outcomes.lose.player,
+            &self.outcomes.draw.player,
+            &self.outcomes.win.player,
+        ];
+        ensure!(
+            players[0] != players[1] && players[1] != players[2] && players[0] != players[2],
+            "outcomes must have different symbols"
+        );
+        Ok(())
+    }
+
+    fn beats(&self, shape: usize, other: usize) -> bool {
+        self.shapes[shape].beats.contains(&self.shapes[other].name)
+    }
+
+    pub fn opponent(&self, symbol: &str) -> Result<usize> {
+        self.shapes
+            .iter()
+            .position(|s| s.opponent == symbol)
+            .ok_or_else(|| unknown_opponent(symbol))
+    }
+
+    /// The shape the strategy `symbol` plays, when it's read as a shape.
+    pub fn shape(&self, symbol: &str) -> Result<usize> {
+        self.shapes
+            .iter()
+            .position(|s| s.player == symbol)
+            .ok_or_else(|| unknown_strategy(symbol))
+    }
+
+    /// The outcome the strategy `symbol` calls for, when it's read as an
+    /// outcome.
+    pub fn desired(&self, symbol: &str) -> Result<Outcome> {
+        Outcome::ALL
+            .into_iter()
+            .find(|&o| self.outcome_rule(o).player == symbol)
+            .ok_or_else(|| unknown_strategy(symbol))
+    }
+
+    pub fn outcome_rule(&self, outcome: Outcome) -> &OutcomeRule {
+        match outcome {
+            Outcome::Lose => &self.outcomes.lose,
+            Outcome::Draw => &self.outcomes.draw,
+            Outcome::Win => &self.outcomes.win,
+        }
+    }
+
+    /// The outcome for the player of playing `shape` against `opponent`.
+    pub fn outcome(&self, shape: usize, opponent: usize) -> Outcome {
+        if shape == opponent {
+            Outcome::Draw
+        } else if self.beats(shape, opponent) {
+            Outcome::Win
+        } else {
+            Outcome::Lose
+        }
+    }
+
+    /// The shape to play against `opponent` for `outcome`: the highest
+    /// scoring one if there's a choice, and the first listed of those.
+    pub fn response(&self, opponent: usize, outcome: Outcome) -> usize {
+        (0..self.shapes.len())
+            .filter(|&shape| self.outcome(shape, opponent) == outcome)
+            .fold(None, |best: Option<usize>, shape| match best {
+                Some(best) if self.shapes[best].score >= self.shapes[shape].score => Some(best),
+                _ => Some(shape),
+            })
+            .expect("every outcome is possible against every shape")
+    }
+
+    /// Every way of reading the strategy's shape symbols as shapes, each
+    /// listing the shape read for each shape's `player` symbol, in order.
+    /// There are n! of them, so games with more than [`MAX_MAPPED_SHAPES`]
+    /// shapes are refused.
+    pub fn mappings(&self) -> Result<Vec<Vec<usize>>> {
+        let n = self.shapes.len();
+        ensure!(
+            n <= MAX_MAPPED_SHAPES,
+            "can't try all {}! mappings of a game with more than {} shapes",
+            n,
+            MAX_MAPPED_SHAPES
+        );
+        Ok((0..n).permutations(n).collect())
+    }
+
+    /// The player's score for a round.
+    pub fn score(&self, shape: usize, opponent: usize) -> i64 {
+        self.shapes[shape].score + self.outcome_rule(self.outcome(shape, opponent)).score
+    }
+}
+
+pub fn unknown_opponent(symbol: &str) -> Error {
+    anyhow!("unknown opponent move: {}", symbol)
+}
+
+pub fn unknown_strategy(symbol: &str) -> Error {
+    anyhow!("unknown strategy: {}", symbol)
+}
+
+/// Each round's opponent shape and strategy symbol.
+fn parse<R: Read>(reader: R, rules: &Rules) -> Result<Vec<(usize, String)>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new).and_then(|c| {
+                let Some((opp, self_)) = c.split_once(' ') else {
+                    bail!("Couldn't parse line");
+                };
+                Ok((rules.opponent(opp)?, self_.to_owned()))
+            })
+        })
+        .collect()
+}
+
+pub fn part1(input: &str, rules: &Rules) -> Result<Answer> {
+    let rounds = parse(input.as_bytes(), rules)?;
+
+    Ok(rounds
+        .iter()
+        .map(|(opp, self_)| Ok(rules.score(rules.shape(self_)?, *opp)))
+        .sum::<Result<i64>>()?
+        .into())
+}
+
+pub fn part2(input: &str, rules: &Rules) -> Result<Answer> {
+    let rounds = parse(input.as_bytes(), rules)?;
+
+    Ok(rounds
+        .iter()
+        .map(|(opp, self_)| Ok(rules.score(rules.response(*opp, rules.desired(self_)?), *opp)))
+        .sum::<Result<i64>>()?
+        .into())
+}
+
+/// How a strategy guide fares against its opponent under every mapping of
+/// its symbols to shapes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    /// The rounds the opponent played each shape in.
+    pub opponent: Vec<i64>,
+    /// Every mapping, highest scoring first, in [`Rules::mappings`] order
+    /// on ties.
+    pub mappings: Vec<Mapping>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    /// The shape read for each shape's `player` symbol.
+    pub shapes: Vec<usize>,
+    /// The total score of following the guide under this mapping.
+    pub total: i64,
+}
+
+impl Analysis {
+    /// Scores every mapping from `totals`, the total score of the rounds with
+    /// each shape's `player` symbol when it's read as each shape, by symbol
+    /// and then shape.
+    pub fn new(rules: &Rules, opponent: Vec<i64>, totals: &[Vec<i64>]) -> Result<Self> {
+        let mut mappings: Vec<Mapping> = rules
+            .mappings()?
+            .into_iter()
+            .map(|shapes| Mapping {
+                total: shapes
+                    .iter()
+                    .enumerate()
+                    .map(|(symbol, &shape)| totals[symbol][shape])
+                    .sum(),
+                shapes,
+            })
+            .collect();
+        mappings.sort_by_key(|m| Reverse(m.total));
+        Ok(Analysis { opponent, mappings })
+    }
+
+    pub fn rounds(&self) -> i64 {
+        self.opponent.iter().sum()
+    }
+
+    /// The highest scoring mapping.
+    pub fn best(&self) -> Option<&Mapping> {
+        self.mappings.first()
+    }
+}
+
+/// Reads the strategy column as shapes, like part 1, and tries every mapping
+/// of its symbols to shapes.
+pub fn analyze(input: &str, rules: &Rules) -> Result<Analysis> {
+    let rounds = parse(input.as_bytes(), rules)?
+        .into_iter()
+        .map(|(opp, self_)| Ok((opp, rules.shape(&self_)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let n = rules.shapes.len();
+    let mut opponent = vec![0; n];
+    let mut totals = vec![vec![0; n]; n];
+    for &(opp, symbol) in &rounds {
+        opponent[opp] += 1;
+        for (shape, total) in totals[symbol].iter_mut().enumerate() {
+            *total += rules.score(shape, opp);
+        }
+    }
+    Analysis::new(rules, opponent, &totals)
 }
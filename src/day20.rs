@@ -1,10 +1,11 @@
 use anyhow::{anyhow, Error, Result};
 use std::{
     cmp::Ordering,
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 fn parse<R: Read>(reader: R) -> Result<Vec<i64>> {
     BufReader::new(reader)
         .lines()
@@ -15,20 +16,9 @@ fn parse<R: Read>(reader: R) -> Result<Vec<i64>> {
         .collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input20.txt")?;
-
-    let numbers = parse(file)?;
-
-    println!("{}", part1(&numbers)?);
-    println!("{}", part2(&numbers)?);
-
-    Ok(())
-}
-
-fn mix(numbers: &mut Vec<(i64, usize)>, n: usize) -> Result<()> {
+fn mix(numbers: &mut [(i64, usize)], n: usize) -> Result<()> {
     let len = numbers.len();
-    let orig = numbers.clone();
+    let orig = numbers.to_vec();
     for _i in 0..n {
         for (number, i) in &orig {
             let curri = numbers
@@ -51,7 +41,8 @@ fn mix(numbers: &mut Vec<(i64, usize)>, n: usize) -> Result<()> {
     Ok(())
 }
 
-fn part1(numbers: &[i64]) -> Result<i64> {
+pub fn part1(input: &str) -> Result<Answer> {
+    let numbers = parse(input.as_bytes())?;
     let mut numbers: Vec<(i64, usize)> = numbers.iter().enumerate().map(|(i, &n)| (n, i)).collect();
     mix(&mut numbers, 1)?;
     let izero = numbers
@@ -59,12 +50,14 @@ fn part1(numbers: &[i64]) -> Result<i64> {
         .position(|(n, _)| *n == 0)
         .ok_or_else(|| anyhow!("couldn't find 0"))?;
     let len = numbers.len();
-    Ok(numbers[(izero + 1000) % len].0
+    Ok((numbers[(izero + 1000) % len].0
         + numbers[(izero + 2000) % len].0
         + numbers[(izero + 3000) % len].0)
+        .into())
 }
 
-fn part2(numbers: &[i64]) -> Result<i64> {
+pub fn part2(input: &str) -> Result<Answer> {
+    let numbers = parse(input.as_bytes())?;
     let key = 811589153;
     let mut numbers: Vec<(i64, usize)> = numbers
         .iter()
@@ -77,7 +70,8 @@ fn part2(numbers: &[i64]) -> Result<i64> {
         .position(|(n, _)| *n == 0)
         .ok_or_else(|| anyhow!("couldn't find 0"))?;
     let len = numbers.len();
-    Ok(numbers[(izero + 1000) % len].0
+    Ok((numbers[(izero + 1000) % len].0
         + numbers[(izero + 2000) % len].0
         + numbers[(izero + 3000) % len].0)
+        .into())
 }
@@ -2,10 +2,11 @@ use anyhow::{anyhow, bail, ensure, Error, Result};
 use regex::Regex;
 use std::{
     collections::HashMap,
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 #[derive(Debug)]
 enum Op {
     Add(String, String),
@@ -43,17 +44,6 @@ fn parse<R: Read>(reader: R) -> Result<HashMap<String, Op>> {
         .collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input21.txt")?;
-
-    let monkeys = parse(file)?;
-
-    println!("{}", part1(&monkeys)?);
-    println!("{}", part2(&monkeys)?);
-
-    Ok(())
-}
-
 fn solve1(monkeys: &HashMap<String, Op>, monkey: &str) -> Result<i64> {
     Ok(
         match monkeys
@@ -69,8 +59,10 @@ fn solve1(monkeys: &HashMap<String, Op>, monkey: &str) -> Result<i64> {
     )
 }
 
-fn part1(monkeys: &HashMap<String, Op>) -> Result<i64> {
-    solve1(monkeys, "root")
+pub fn part1(input: &str) -> Result<Answer> {
+    let monkeys = parse(input.as_bytes())?;
+
+    Ok(solve1(&monkeys, "root")?.into())
 }
 
 fn find_humn<'a>(
@@ -211,7 +203,10 @@ fn solve2(
     )
 }
 
-fn part2(monkeys: &HashMap<String, Op>) -> Result<i64> {
+pub fn part2(input: &str) -> Result<Answer> {
+    let monkeys = parse(input.as_bytes())?;
+    let monkeys = &monkeys;
+
     let (m1, m2) = match monkeys.get("root") {
         Some(Op::Add(m1, m2)) => (m1, m2),
         Some(Op::Sub(m1, m2)) => (m1, m2),
@@ -225,7 +220,7 @@ fn part2(monkeys: &HashMap<String, Op>) -> Result<i64> {
         (Some(path), None) => {
             let target = solve1(monkeys, m2)?;
             if let Some(t) = solve2(monkeys, m1, path, target)? {
-                Ok(t)
+                Ok(t.into())
             } else {
                 bail!("unexpected val when computing top level target")
             }
@@ -233,7 +228,7 @@ fn part2(monkeys: &HashMap<String, Op>) -> Result<i64> {
         (None, Some(path)) => {
             let target = solve1(monkeys, m1)?;
             if let Some(t) = solve2(monkeys, m2, path, target)? {
-                Ok(t)
+                Ok(t.into())
             } else {
                 bail!("unexpected val when computing top level target")
             }
@@ -1,9 +1,8 @@
 use anyhow::{anyhow, bail, Error, Result};
 use itertools::Itertools;
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Read},
-};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
 
 #[derive(Debug, PartialEq)]
 enum Tile {
@@ -92,18 +91,9 @@ fn parse<R: Read>(reader: R) -> Result<(Vec<Vec<Tile>>, Vec<Instruction>)> {
     Ok((board, instructions))
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input22.txt")?;
-
-    let (board, directions) = parse(file)?;
-
-    println!("{}", part1(&board, &directions)?);
-    println!("{}", part2(&board, &directions)?);
+pub fn part1(input: &str) -> Result<Answer> {
+    let (board, instructions) = parse(input.as_bytes())?;
 
-    Ok(())
-}
-
-fn part1(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
     let rows = board
         .iter()
         .map(|row| {
@@ -113,7 +103,7 @@ fn part1(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
                     .ok_or_else(|| anyhow!("couldn't find first open space in row: {:?}", row))?,
                 row.iter()
                     .positions(|s| *s != Tile::None)
-                    .last()
+                    .next_back()
                     .ok_or_else(|| anyhow!("couldn't find last open space in row: {:?}", row))?,
             ))
         })
@@ -146,7 +136,7 @@ fn part1(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
     let mut row = 0;
     let mut col = rows[0].0;
     let mut facing = '>';
-    for instruction in instructions {
+    for instruction in &instructions {
         match instruction {
             Instruction::Left => {
                 facing = match facing {
@@ -221,7 +211,7 @@ fn part1(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
             },
         }
     }
-    Ok(1000 * (i64::try_from(row)? + 1)
+    Ok((1000 * (i64::try_from(row)? + 1)
         + 4 * (i64::try_from(col)? + 1)
         + match facing {
             '>' => 0,
@@ -230,16 +220,19 @@ fn part1(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
             '^' => 3,
             _ => bail!("unknown facing: {}", facing),
         })
+    .into())
 }
 
-fn part2(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
+pub fn part2(input: &str) -> Result<Answer> {
+    let (board, instructions) = parse(input.as_bytes())?;
+
     let mut row = 0;
     let mut col = board[0]
         .iter()
         .position(|t| *t != Tile::None)
         .ok_or_else(|| anyhow!("couldn't find starting col"))?;
     let mut facing = '>';
-    for instruction in instructions {
+    for instruction in &instructions {
         match instruction {
             Instruction::Left => {
                 facing = match facing {
@@ -336,7 +329,7 @@ fn part2(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
             }
         }
     }
-    Ok(1000 * (i64::try_from(row)? + 1)
+    Ok((1000 * (i64::try_from(row)? + 1)
         + 4 * (i64::try_from(col)? + 1)
         + match facing {
             '>' => 0,
@@ -345,4 +338,5 @@ fn part2(board: &[Vec<Tile>], instructions: &[Instruction]) -> Result<i64> {
             '^' => 3,
             _ => bail!("unknown facing: {}", facing),
         })
+    .into())
 }
@@ -1,23 +1,25 @@
 use anyhow::{bail, Result};
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 fn parse<R: Read>(reader: R) -> Result<Vec<String>, std::io::Error> {
     BufReader::new(reader).lines().collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input23.txt")?;
+pub fn part1(input: &str) -> Result<Answer> {
+    let board = parse(input.as_bytes())?;
 
-    let board = parse(file)?;
+    Ok(solve(&board, 10)?.1.into())
+}
 
-    println!("{}", solve(&board, 10)?.1);
-    println!("{}", solve(&board, 0)?.0);
+pub fn part2(input: &str) -> Result<Answer> {
+    let board = parse(input.as_bytes())?;
 
-    Ok(())
+    Ok(Answer::try_from(solve(&board, 0)?.0)?)
 }
 
 fn solve(board: &[String], nrounds: usize) -> Result<(usize, i64)> {
@@ -1,11 +1,12 @@
 use anyhow::{bail, Error, Result};
 use std::{
     collections::{BinaryHeap, HashMap, HashSet},
-    fs::File,
     hash::Hash,
     io::{BufRead, BufReader, Read},
 };
 
+use crate::Answer;
+
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
 enum Dir {
     L,
@@ -52,20 +53,11 @@ fn parse<R: Read>(reader: R) -> Result<Vec<Vec<Option<Dir>>>> {
         .collect()
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input24.txt")?;
-
-    let board = parse(file)?;
-
-    println!("{}", part1(&board)?);
-    println!("{}", part2(&board)?);
-
-    Ok(())
-}
+pub fn part1(input: &str) -> Result<Answer> {
+    let board = parse(input.as_bytes())?;
 
-fn part1(board: &[Vec<Option<Dir>>]) -> Result<usize> {
-    solve(
-        board,
+    Ok(Answer::try_from(solve(
+        &board,
         State {
             x: 0,
             y: -1,
@@ -73,7 +65,7 @@ fn part1(board: &[Vec<Option<Dir>>]) -> Result<usize> {
         },
         i64::try_from(board[0].len())? - 1,
         i64::try_from(board.len())?,
-    )
+    )?)?)
 }
 
 fn solve(board: &[Vec<Option<Dir>>], init: State, goalx: i64, goaly: i64) -> Result<usize> {
@@ -247,7 +239,10 @@ fn get_map(
         .collect()
 }
 
-fn part2(board: &[Vec<Option<Dir>>]) -> Result<usize> {
+pub fn part2(input: &str) -> Result<Answer> {
+    let board = parse(input.as_bytes())?;
+    let board = &board;
+
     let maxx = i64::try_from(board[0].len())?;
     let maxy = i64::try_from(board.len())?;
     let time = solve(
@@ -270,5 +265,10 @@ fn part2(board: &[Vec<Option<Dir>>]) -> Result<usize> {
         0,
         -1,
     )?;
-    solve(board, State { x: 0, y: -1, time }, maxx - 1, maxy)
+    Ok(Answer::try_from(solve(
+        board,
+        State { x: 0, y: -1, time },
+        maxx - 1,
+        maxy,
+    )?)?)
 }
@@ -1,8 +1,7 @@
 use anyhow::{bail, Result};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Read},
-};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
 
 fn parse<R: Read>(reader: R) -> Result<Vec<String>> {
     Ok(BufReader::new(reader)
@@ -10,20 +9,11 @@ fn parse<R: Read>(reader: R) -> Result<Vec<String>> {
         .collect::<Result<Vec<String>, std::io::Error>>()?)
 }
 
-fn main() -> Result<()> {
-    let file = File::open("input25.txt")?;
-
-    let numbers = parse(file)?;
+pub fn part1(input: &str) -> Result<Answer> {
+    let numbers = parse(input.as_bytes())?;
 
-    println!("{}", part1(&numbers)?);
-    println!("{}", part2(&numbers)?);
-
-    Ok(())
-}
-
-fn part1(numbers: &[String]) -> Result<String> {
     let mut sum = 0i64;
-    for number in numbers {
+    for number in &numbers {
         let mut val = 0;
         for c in number.chars() {
             val *= 5;
@@ -59,7 +49,7 @@ fn part1(numbers: &[String]) -> Result<String> {
             break;
         }
     }
-    digits
+    Ok(digits
         .iter()
         .map(|d| {
             Ok(match d {
@@ -71,9 +61,6 @@ fn part1(numbers: &[String]) -> Result<String> {
                 _ => bail!("uexpected c"),
             })
         })
-        .collect::<Result<String>>()
-}
-
-fn part2(_numbers: &[String]) -> Result<usize> {
-    bail!("unimplemented")
+        .collect::<Result<String>>()?
+        .into())
 }
@@ -1,220 +1,168 @@
-use anyhow::{Error, Result};
-use futures::stream::TryStreamExt;
-use mongodb::{
-    bson::{doc, Bson, Document},
-    options::ClientOptions,
-    Client,
-};
+use anyhow::{bail, ensure, Error, Result};
+use itertools::Itertools;
+use serde::Deserialize;
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+    collections::HashSet,
+    fmt,
+    io::{BufRead, BufReader, Read},
 };
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let file = File::open("input3.txt")?;
-    let lines = BufReader::new(file).lines();
-
-    let client_options = ClientOptions::parse("mongodb://localhost").await?;
-    let client = Client::with_options(client_options)?;
-
-    let database = client.database("aoc2022");
-
-    let input3 = database.collection::<Document>("input3");
-
-    input3.drop(None).await?;
-
-    input3
-        .insert_many(
-            lines
-                .map(|l| {
-                    l.map_err(Error::new).map(|items| {
-                        doc! { "items": items }
-                    })
-                })
-                .collect::<Result<Vec<Document>>>()?,
-            None,
-        )
-        .await?;
-
-    let mut cursor = input3
-        .aggregate(
-            [doc! {
-                "$project": doc! {
-                    "c1": doc! {
-                        "$substrBytes": [ "$items", 0, doc! {
-                            "$divide": [ doc! { "$strLenBytes": "$items" }, 2 ],
-                        } ],
-                    },
-                    "c2": doc! {
-                        "$substrBytes": [ "$items", doc! {
-                            "$divide": [ doc! { "$strLenBytes": "$items" }, 2 ],
-                        }, -1 ],
-                    },
-                },
-            }, doc! {
-                "$project": doc! {
-                    "c1": doc! {
-                        "$map": doc! {
-                            "input": doc! {
-                                "$range": [0, doc! { "$strLenBytes": "$c1" }],
-                            },
-                            "in": doc! {
-                                "$substrBytes": [ "$c1", "$$this", 1 ],
-                            },
-                        },
-                    },
-                    "c2": doc! {
-                        "$map": doc! {
-                            "input": doc! {
-                                "$range": [0, doc! { "$strLenBytes": "$c2" }],
-                            },
-                            "in": doc! {
-                                "$substrBytes": [ "$c2", "$$this", 1 ],
-                            },
-                        },
-                    },
-                },
-            }, doc! {
-                "$group": doc! {
-                    "_id": Bson::Null,
-                    "prioritySum": doc! {
-                        "$sum": doc! {
-                            "$function": doc! {
-                                "body": "function(c) {
-  return (c.toUpperCase() === c) ? c.charCodeAt(0) - 'A'.charCodeAt(0) + 27 : c.charCodeAt(0) - 'a'.charCodeAt(0) + 1
-}",
-                                "args": [ doc! {
-                                    "$first": doc! {
-                                        "$setIntersection": [ "$c1", "$c2" ],
-                                    },
-                                } ],
-                                "lang": "js",
-                            },
-                        },
-                    },
-                },
-            }],
-            None,
-        )
-        .await?;
-    while let Some(result) = cursor.try_next().await? {
-        println!("{}", result);
+use crate::Answer;
+
+/// Items in priority order, lowest first.
+pub const DEFAULT_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// How rucksacks are grouped and their items prioritized, on every backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Rucksacks per group in part 2.
+    pub group_size: usize,
+    /// Items in priority order, lowest first.
+    pub alphabet: String,
+}
+
+impl Config {
+    /// Checks that groups have rucksacks and the alphabet has each item once.
+    pub fn new(group_size: usize, alphabet: String) -> Result<Self> {
+        ensure!(group_size > 0, "groups need at least one rucksack");
+        ensure!(!alphabet.is_empty(), "the alphabet can't be empty");
+        if let Some(c) = alphabet.chars().duplicates().next() {
+            bail!("{} is in the alphabet twice", c);
+        }
+        Ok(Config {
+            group_size,
+            alphabet,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            group_size: 3,
+            alphabet: DEFAULT_ALPHABET.to_string(),
+        }
     }
+}
+
+pub const ODD: &str = "an odd number of items";
+pub const NO_COMMON_ITEM: &str = "no common item";
+pub const SEVERAL_COMMON_ITEMS: &str = "more than one common item";
+
+pub fn short_group(group_size: usize) -> String {
+    format!("fewer than {} rucksacks in the group", group_size)
+}
+
+pub fn not_in_alphabet(item: &str) -> String {
+    format!("common item {} is not in the alphabet", item)
+}
+
+/// A rucksack, or group of them, that can't be scored.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Problem {
+    /// The 1-based lines of the first and last rucksack.
+    pub first: i64,
+    pub last: i64,
+    pub message: String,
+}
 
-    let mut cursor = input3
-        .aggregate(
-            [
-                doc! {
-                    "$setWindowFields": doc! {
-                        "sortBy": doc! {
-                            "_id": 1,
-                        },
-                        "output": doc! {
-                            "i": doc! {
-                                "$count": doc! {},
-                                "window": doc! {
-                                    "documents": ["unbounded", -1],
-                                }
-                            },
-                        },
-                    },
-                },
-                doc! {
-                    "$group": {
-                        "_id": doc! {
-                            "$divide": [
-                                doc! {
-                                    "$subtract": [
-                                        "$i",
-                                        doc! {
-                                            "$mod": ["$i", 3],
-                                        },
-                                    ],
-                                },
-                                3,
-                            ],
-                        },
-                        "groupItems": doc! {
-                            "$push": "$items",
-                        },
-                    },
-                },
-                doc! {
-                    "$project": {
-                        "groupBadge": doc! {
-                            "$first": doc! {
-                                "$setIntersection": [
-                                    doc! {
-                                        "$map": doc! {
-                                            "input": doc! {
-                                                "$range": [0, doc! { "$strLenBytes": doc! {
-                                                    "$arrayElemAt": ["$groupItems", 0],
-                                                }}],
-                                            },
-                                            "in": doc! {
-                                                "$substrBytes": [ doc! {
-                                                    "$arrayElemAt": ["$groupItems", 0],
-                                                }, "$$this", 1 ],
-                                            },
-                                        },
-                                    },
-                                    doc! {
-                                        "$map": doc! {
-                                            "input": doc! {
-                                                "$range": [0, doc! { "$strLenBytes": doc! {
-                                                    "$arrayElemAt": ["$groupItems", 1],
-                                                }}],
-                                            },
-                                            "in": doc! {
-                                                "$substrBytes": [ doc! {
-                                                    "$arrayElemAt": ["$groupItems", 1],
-                                                }, "$$this", 1 ],
-                                            },
-                                        },
-                                    },
-                                    doc! {
-                                        "$map": doc! {
-                                            "input": doc! {
-                                                "$range": [0, doc! { "$strLenBytes": doc! {
-                                                    "$arrayElemAt": ["$groupItems", 2],
-                                                }}],
-                                            },
-                                            "in": doc! {
-                                                "$substrBytes": [ doc! {
-                                                    "$arrayElemAt": ["$groupItems", 2],
-                                                }, "$$this", 1 ],
-                                            },
-                                        },
-                                    },
-                                ],
-                            },
-                        },
-                    },
-                },
-                doc! {
-                    "$group": doc! {
-                        "_id": Bson::Null,
-                        "prioritySum": doc! {
-                            "$sum": doc! {
-                                "$function": doc! {
-                                    "body": "function(c) {
-  return (c.toUpperCase() === c) ? c.charCodeAt(0) - 'A'.charCodeAt(0) + 27 : c.charCodeAt(0) - 'a'.charCodeAt(0) + 1
-}",
-                                    "args": [ "$groupBadge" ],
-                                    "lang": "js",
-                                },
-                            },
-                        },
-                    },
-                },
-            ],
-            None,
-        )
-        .await?;
-    while let Some(result) = cursor.try_next().await? {
-        println!("{}", result);
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first == self.last {
+            write!(f, "line {}: {}", self.first, self.message)
+        } else {
+            write!(f, "lines {}-{}: {}", self.first, self.last, self.message)
+        }
     }
+}
+
+/// Fails listing every problem, one per line, if there are any.
+pub fn check(problems: &[Problem]) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    bail!("couldn't score the input:\n{}", problems.iter().join("\n"))
+}
+
+fn parse<R: Read>(reader: R) -> Result<Vec<String>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| l.map_err(Error::new))
+        .collect()
+}
+
+/// The priority of the one item common to all `rucksacks`.
+fn badge(rucksacks: &[&str], alphabet: &str) -> Result<u32, String> {
+    let common = rucksacks
+        .iter()
+        .map(|r| r.chars().collect::<HashSet<char>>())
+        .reduce(|acc, s| acc.intersection(&s).copied().collect())
+        .unwrap_or_default();
+    let item = match common.into_iter().collect::<Vec<_>>()[..] {
+        [] => return Err(NO_COMMON_ITEM.to_string()),
+        [item] => item,
+        _ => return Err(SEVERAL_COMMON_ITEMS.to_string()),
+    };
+    alphabet
+        .chars()
+        .position(|c| c == item)
+        .map(|i| i as u32 + 1)
+        .ok_or_else(|| not_in_alphabet(&item.to_string()))
+}
+
+/// Sums the priorities of the rucksacks or groups of `n` starting at each
+/// 0-based line, failing if any can't be scored.
+fn priority_sum(
+    results: impl Iterator<Item = (usize, usize, Result<u32, String>)>,
+) -> Result<Answer> {
+    let mut sum = 0;
+    let mut problems = vec![];
+    for (line, n, result) in results {
+        match result {
+            Ok(priority) => sum += priority,
+            Err(message) => problems.push(Problem {
+                first: line as i64 + 1,
+                last: (line + n) as i64,
+                message,
+            }),
+        }
+    }
+    check(&problems)?;
+    Ok(sum.into())
+}
+
+pub fn part1(input: &str, config: &Config) -> Result<Answer> {
+    let rucksacks = parse(input.as_bytes())?;
+
+    priority_sum(rucksacks.iter().enumerate().map(|(i, items)| {
+        let n = items.chars().count();
+        let result = if n % 2 == 1 {
+            Err(ODD.to_string())
+        } else {
+            let half = items
+                .char_indices()
+                .nth(n / 2)
+                .map_or(items.len(), |(i, _)| i);
+            let (c1, c2) = items.split_at(half);
+            badge(&[c1, c2], &config.alphabet)
+        };
+        (i, 1, result)
+    }))
+}
+
+pub fn part2(input: &str, config: &Config) -> Result<Answer> {
+    let group_size = config.group_size;
+    let rucksacks = parse(input.as_bytes())?;
 
-    Ok(())
+    priority_sum(rucksacks.chunks(group_size).enumerate().map(|(i, group)| {
+        let result = if group.len() < group_size {
+            Err(short_group(group_size))
+        } else {
+            badge(
+                &group.iter().map(String::as_str).collect::<Vec<_>>(),
+                &config.alphabet,
+            )
+        };
+        (i * group_size, group.len(), result)
+    }))
 }
@@ -1,147 +1,47 @@
-use anyhow::{Error, Result};
-use futures::stream::TryStreamExt;
-use mongodb::{
-    bson::{doc, Bson, Document},
-    options::ClientOptions,
-    Client,
-};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let file = File::open("input4.txt")?;
-    let lines = BufReader::new(file).lines();
-
-    let client_options = ClientOptions::parse("mongodb://localhost").await?;
-    let client = Client::with_options(client_options)?;
-
-    let database = client.database("aoc2022");
-
-    let input4 = database.collection::<Document>("input4");
+use anyhow::{anyhow, Error, Result};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
+
+#[allow(clippy::type_complexity)]
+fn parse<R: Read>(reader: R) -> Result<Vec<((i64, i64), (i64, i64))>> {
+    let range = |r: &str| -> Result<(i64, i64)> {
+        let (s, e) = r
+            .split_once('-')
+            .ok_or_else(|| anyhow!("couldn't split range: {}", r))?;
+        Ok((s.parse()?, e.parse()?))
+    };
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new).and_then(|pairs| {
+                let (r1, r2) = pairs
+                    .split_once(',')
+                    .ok_or_else(|| anyhow!("couldn't split pair: {}", pairs))?;
+                Ok((range(r1)?, range(r2)?))
+            })
+        })
+        .collect()
+}
 
-    input4.drop(None).await?;
+pub fn part1(input: &str) -> Result<Answer> {
+    let pairs = parse(input.as_bytes())?;
 
-    input4
-        .insert_many(
-            lines
-                .map(|l| {
-                    l.map_err(Error::new).map(|items| {
-                        doc! { "pairs": items }
-                    })
-                })
-                .collect::<Result<Vec<Document>>>()?,
-            None,
-        )
-        .await?;
+    Ok(Answer::try_from(
+        pairs
+            .iter()
+            .filter(|((s1, e1), (s2, e2))| (s2 >= s1 && e2 <= e1) || (s1 >= s2 && e1 <= e2))
+            .count(),
+    )?)
+}
 
-    let mut cursor = input4
-        .aggregate(
-            [
-                doc! {
-                    "$project": doc! {
-                        "s1": doc! {
-                            "$toInt": doc! {
-                                "$arrayElemAt": [ doc! {
-                                    "$split": [ doc! {
-                                        "$arrayElemAt": [ doc! {
-                                            "$split": [ "$pairs", "," ],
-                                        }, 0],
-                                    }, "-" ],
-                                }, 0],
-                            },
-                        },
-                        "e1": doc! {
-                            "$toInt": doc! {
-                                "$arrayElemAt": [ doc! {
-                                    "$split": [ doc! {
-                                        "$arrayElemAt": [ doc! {
-                                            "$split": [ "$pairs", "," ],
-                                        }, 0],
-                                    }, "-" ],
-                                }, 1],
-                            },
-                        },
-                        "s2": doc! {
-                            "$toInt": doc! {
-                                "$arrayElemAt": [ doc! {
-                                    "$split": [ doc! {
-                                        "$arrayElemAt": [ doc! {
-                                            "$split": [ "$pairs", "," ],
-                                        }, 1],
-                                    }, "-" ],
-                                }, 0],
-                            },
-                        },
-                        "e2": doc! {
-                            "$toInt": doc! {
-                                "$arrayElemAt": [ doc! {
-                                    "$split": [ doc! {
-                                        "$arrayElemAt": [ doc! {
-                                            "$split": [ "$pairs", "," ],
-                                        }, 1],
-                                    }, "-" ],
-                                }, 1],
-                            },
-                        },
-                    },
-                },
-                doc! {
-                    "$group": doc! {
-                        "_id": Bson::Null,
-                        "numContained": doc! {
-                            "$sum": doc! {
-                                "$toInt": doc! {
-                                    "$or": [
-                                        doc! {
-                                            "$and": [
-                                                doc! {
-                                                    "$gte": [ "$s2", "$s1" ],
-                                                },
-                                                doc! {
-                                                    "$lte": [ "$e2", "$e1" ],
-                                                },
-                                            ],
-                                        },
-                                        doc! {
-                                            "$and": [
-                                                doc! {
-                                                    "$gte": [ "$s1", "$s2" ],
-                                                },
-                                                doc! {
-                                                    "$lte": [ "$e1", "$e2" ],
-                                                },
-                                            ],
-                                        },
-                                    ],
-                                },
-                            },
-                        },
-                        "numOverlapping": doc! {
-                            "$sum": doc! {
-                                "$toInt": doc! {
-                                    "$and": [
-                                        doc! {
-                                            "$lte": [ "$s1", "$e2" ],
-                                        },
-                                        doc! {
-                                            "$lte": [ "$s2", "$e1" ],
-                                        },
-                                    ],
-                                },
-                            },
-                        },
-                    },
-                },
-            ],
-            None,
-        )
-        .await?;
-    while let Some(result) = cursor.try_next().await? {
-        println!("{}", result);
-    }
+pub fn part2(input: &str) -> Result<Answer> {
+    let pairs = parse(input.as_bytes())?;
 
-    Ok(())
+    Ok(Answer::try_from(
+        pairs
+            .iter()
+            .filter(|((s1, e1), (s2, e2))| s1 <= e2 && s2 <= e1)
+            .count(),
+    )?)
 }
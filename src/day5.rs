@@ -1,13 +1,12 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::{BufRead, BufReader, Read};
 
-fn main() -> Result<()> {
-    let file = File::open("input5.txt")?;
-    let mut lines = BufReader::new(file).lines();
+use crate::Answer;
+
+#[allow(clippy::type_complexity)]
+fn parse<R: Read>(reader: R) -> Result<(Vec<Vec<char>>, Vec<(usize, usize, usize)>)> {
+    let mut lines = BufReader::new(reader).lines();
 
     let mut stacks = vec![];
     while let Some(Ok(line)) = lines.next() {
@@ -30,70 +29,51 @@ fn main() -> Result<()> {
 
     stacks.iter_mut().for_each(|s| s.reverse());
 
-    while let Some(Ok(line)) = lines.next() {
-        let s: Vec<&str> = line.split(' ').collect();
-        let (n, src, dst) = (
-            s[1].parse::<usize>()?,
-            s[3].parse::<usize>()?,
-            s[5].parse::<usize>()?,
-        );
+    let moves = lines
+        .map(|l| {
+            l.map_err(Error::new).and_then(|line| {
+                let s: Vec<&str> = line.split(' ').collect();
+                Ok((
+                    s[1].parse::<usize>()?,
+                    s[3].parse::<usize>()?,
+                    s[5].parse::<usize>()?,
+                ))
+            })
+        })
+        .collect::<Result<Vec<(usize, usize, usize)>>>()?;
+
+    Ok((stacks, moves))
+}
+
+fn tops(stacks: Vec<Vec<char>>) -> Result<Answer> {
+    Ok(stacks
+        .into_iter()
+        .map(|s| s.last().copied().ok_or_else(|| anyhow!("empty vec")))
+        .collect::<Result<String>>()?
+        .into())
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let (mut stacks, moves) = parse(input.as_bytes())?;
+
+    for (n, src, dst) in moves {
         let i = stacks[src - 1].len() - n;
         let mut to_move = stacks[src - 1].split_off(i);
         to_move.reverse();
         stacks[dst - 1].extend_from_slice(&to_move);
     }
 
-    println!(
-        "{}",
-        stacks
-            .into_iter()
-            .map(|s| s.last().copied().ok_or_else(|| anyhow!("empty vec")))
-            .collect::<Result<String>>()?
-    );
-
-    let file = File::open("input5.txt")?;
-    let mut lines = BufReader::new(file).lines();
-
-    let mut stacks = vec![];
-    while let Some(Ok(line)) = lines.next() {
-        if line.is_empty() {
-            break;
-        }
-        let n = (line.len() - 3) / 4 + 1;
-        if stacks.is_empty() {
-            (0..n).for_each(|_| stacks.push(vec![]));
-        }
-        for (i, mut chunk) in line.chars().chunks(4).into_iter().enumerate() {
-            let c = chunk
-                .nth(1)
-                .ok_or_else(|| anyhow!("couldn't unwrap char"))?;
-            if c != ' ' && !c.is_ascii_digit() {
-                stacks[i].push(c);
-            }
-        }
-    }
+    tops(stacks)
+}
 
-    stacks.iter_mut().for_each(|s| s.reverse());
+pub fn part2(input: &str) -> Result<Answer> {
+    let (mut stacks, moves) = parse(input.as_bytes())?;
 
-    while let Some(Ok(line)) = lines.next() {
-        let s: Vec<&str> = line.split(' ').collect();
-        let (n, src, dst) = (
-            s[1].parse::<usize>()?,
-            s[3].parse::<usize>()?,
-            s[5].parse::<usize>()?,
-        );
+    for (n, src, dst) in moves {
         let i = stacks[src - 1].len() - n;
         let to_move = stacks[src - 1].split_off(i);
         stacks[dst - 1].extend_from_slice(&to_move);
     }
 
-    println!(
-        "{}",
-        stacks
-            .into_iter()
-            .map(|s| s.last().copied().ok_or_else(|| anyhow!("empty vec")))
-            .collect::<Result<String>>()?
-    );
-
-    Ok(())
+    tops(stacks)
 }
@@ -1,24 +1,32 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
-use std::fs::read_to_string;
+use std::io::Read;
 
-fn main() -> Result<()> {
-    let mut buf = read_to_string("input6.txt")?;
-    buf.pop();
+use crate::Answer;
 
-    let vec = buf.chars().collect::<Vec<char>>();
-    for (i, win) in vec.windows(4).enumerate() {
-        if win.iter().unique().count() == 4 {
-            println!("{}", i + 4);
-            break;
-        }
-    }
-    for (i, win) in vec.windows(14).enumerate() {
-        if win.iter().unique().count() == 14 {
-            println!("{}", i + 14);
-            break;
+fn parse<R: Read>(mut reader: R) -> Result<Vec<char>> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    Ok(buf.trim_end().chars().collect())
+}
+
+fn find_marker(vec: &[char], n: usize) -> Result<usize> {
+    for (i, win) in vec.windows(n).enumerate() {
+        if win.iter().unique().count() == n {
+            return Ok(i + n);
         }
     }
+    Err(anyhow!("couldn't find a marker of length {}", n))
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let buf = parse(input.as_bytes())?;
+
+    Ok(Answer::try_from(find_marker(&buf, 4)?)?)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let buf = parse(input.as_bytes())?;
 
-    Ok(())
+    Ok(Answer::try_from(find_marker(&buf, 14)?)?)
 }
@@ -2,13 +2,13 @@ use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::{
     collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
 };
 
-fn main() -> Result<()> {
-    let file = File::open("input7.txt")?;
-    let mut lines = BufReader::new(file).lines();
+use crate::Answer;
+
+fn parse<R: Read>(reader: R) -> Result<HashMap<String, u32>> {
+    let mut lines = BufReader::new(reader).lines();
 
     let mut stack = vec![];
     let mut sizes: HashMap<String, u32> = HashMap::new();
@@ -38,14 +38,22 @@ fn main() -> Result<()> {
         }
     }
 
-    println!(
-        "{}",
-        sizes
-            .iter()
-            .filter(|(path, size)| path.ends_with('/') && **size <= 100000)
-            .map(|(_path, size)| size)
-            .sum::<u32>()
-    );
+    Ok(sizes)
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let sizes = parse(input.as_bytes())?;
+
+    Ok(sizes
+        .iter()
+        .filter(|(path, size)| path.ends_with('/') && **size <= 100000)
+        .map(|(_path, size)| size)
+        .sum::<u32>()
+        .into())
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let sizes = parse(input.as_bytes())?;
 
     let space_needed = 30000000
         - (70000000
@@ -53,15 +61,12 @@ fn main() -> Result<()> {
                 .get("/")
                 .ok_or_else(|| anyhow!("couldn't find root dir"))?);
 
-    println!(
-        "{}",
-        sizes
-            .iter()
-            .filter(|(path, size)| path.ends_with('/') && **size > space_needed)
-            .map(|(_path, size)| size)
-            .min()
-            .ok_or_else(|| anyhow!("couldn't find a min size"))?
-    );
-
-    Ok(())
+    Ok(sizes
+        .iter()
+        .filter(|(path, size)| path.ends_with('/') && **size > space_needed)
+        .map(|(_path, size)| size)
+        .min()
+        .ok_or_else(|| anyhow!("couldn't find a min size"))?
+        .to_owned()
+        .into())
 }
@@ -1,16 +1,15 @@
 use anyhow::{anyhow, Error, Result};
 use itertools::izip;
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::repeat,
+    io::{BufRead, BufReader, Read},
+    iter::repeat_n,
 };
 
-fn main() -> Result<()> {
-    let file = File::open("input8.txt")?;
-    let lines = BufReader::new(file).lines();
+use crate::Answer;
 
-    let grid: Vec<Vec<i32>> = lines
+fn parse<R: Read>(reader: R) -> Result<Vec<Vec<i32>>> {
+    BufReader::new(reader)
+        .lines()
         .map(|l| {
             l.map_err(Error::new).and_then(|l| {
                 l.chars()
@@ -22,19 +21,21 @@ fn main() -> Result<()> {
                     .collect::<Result<Vec<i32>>>()
             })
         })
-        .collect::<Result<Vec<Vec<i32>>>>()?;
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let grid = parse(input.as_bytes())?;
 
     let h = grid.len();
     let w = grid[0].len();
 
-    let mut top_top = repeat(-1).take(w).collect::<Vec<i32>>();
-    let mut viz_top = repeat(repeat(false).take(w).collect::<Vec<bool>>())
-        .take(h)
-        .collect::<Vec<Vec<bool>>>();
-    let mut top_bot = repeat(-1).take(w).collect::<Vec<i32>>();
-    let mut viz_bot = repeat(repeat(false).take(w).collect::<Vec<bool>>())
-        .take(h)
-        .collect::<Vec<Vec<bool>>>();
+    let mut top_top = repeat_n(-1, w).collect::<Vec<i32>>();
+    let mut viz_top =
+        repeat_n(repeat_n(false, w).collect::<Vec<bool>>(), h).collect::<Vec<Vec<bool>>>();
+    let mut top_bot = repeat_n(-1, w).collect::<Vec<i32>>();
+    let mut viz_bot =
+        repeat_n(repeat_n(false, w).collect::<Vec<bool>>(), h).collect::<Vec<Vec<bool>>>();
 
     for i in 0..h {
         for j in 0..w {
@@ -49,14 +50,12 @@ fn main() -> Result<()> {
         }
     }
 
-    let mut top_l = repeat(-1).take(h).collect::<Vec<i32>>();
-    let mut viz_l = repeat(repeat(false).take(h).collect::<Vec<bool>>())
-        .take(w)
-        .collect::<Vec<Vec<bool>>>();
-    let mut top_r = repeat(-1).take(h).collect::<Vec<i32>>();
-    let mut viz_r = repeat(repeat(false).take(h).collect::<Vec<bool>>())
-        .take(w)
-        .collect::<Vec<Vec<bool>>>();
+    let mut top_l = repeat_n(-1, h).collect::<Vec<i32>>();
+    let mut viz_l =
+        repeat_n(repeat_n(false, h).collect::<Vec<bool>>(), w).collect::<Vec<Vec<bool>>>();
+    let mut top_r = repeat_n(-1, h).collect::<Vec<i32>>();
+    let mut viz_r =
+        repeat_n(repeat_n(false, h).collect::<Vec<bool>>(), w).collect::<Vec<Vec<bool>>>();
 
     for i in 0..w {
         for j in 0..h {
@@ -79,14 +78,17 @@ fn main() -> Result<()> {
         })
         .sum();
 
-    println!("{}", visible_trees);
+    Ok(Answer::try_from(visible_trees)?)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let grid = parse(input.as_bytes())?;
 
-    let mut score_l = repeat(repeat(0).take(h).collect::<Vec<i32>>())
-        .take(w)
-        .collect::<Vec<Vec<i32>>>();
-    let mut score_r = repeat(repeat(0).take(h).collect::<Vec<i32>>())
-        .take(w)
-        .collect::<Vec<Vec<i32>>>();
+    let h = grid.len();
+    let w = grid[0].len();
+
+    let mut score_l = repeat_n(repeat_n(0, h).collect::<Vec<i32>>(), w).collect::<Vec<Vec<i32>>>();
+    let mut score_r = repeat_n(repeat_n(0, h).collect::<Vec<i32>>(), w).collect::<Vec<Vec<i32>>>();
 
     for i in 0..h {
         let mut lstack: Vec<(i32, usize)> = vec![];
@@ -122,12 +124,8 @@ fn main() -> Result<()> {
         }
     }
 
-    let mut score_u = repeat(repeat(0).take(h).collect::<Vec<i32>>())
-        .take(w)
-        .collect::<Vec<Vec<i32>>>();
-    let mut score_d = repeat(repeat(0).take(h).collect::<Vec<i32>>())
-        .take(w)
-        .collect::<Vec<Vec<i32>>>();
+    let mut score_u = repeat_n(repeat_n(0, h).collect::<Vec<i32>>(), w).collect::<Vec<Vec<i32>>>();
+    let mut score_d = repeat_n(repeat_n(0, h).collect::<Vec<i32>>(), w).collect::<Vec<Vec<i32>>>();
 
     for i in 0..w {
         let mut ustack: Vec<(i32, usize)> = vec![];
@@ -173,7 +171,5 @@ fn main() -> Result<()> {
         .max()
         .ok_or_else(|| anyhow!("no max"))?;
 
-    println!("{}", max_score);
-
-    Ok(())
+    Ok(max_score.into())
 }
@@ -1,34 +1,42 @@
 use anyhow::{anyhow, Error, Result};
 use std::{
     collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
 };
 
-fn main() -> Result<()> {
-    let file = File::open("input9.txt")?;
-    let lines = BufReader::new(file)
+use crate::Answer;
+
+fn parse<R: Read>(reader: R) -> Result<Vec<((i32, i32), i32)>> {
+    BufReader::new(reader)
         .lines()
-        .map(|l| l.map_err(Error::new))
-        .collect::<Result<Vec<String>>>()?;
+        .map(|l| {
+            l.map_err(Error::new).and_then(|line| {
+                let (op, n_str) = line
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("couldn't split"))?;
+                let n = n_str.parse::<i32>()?;
+                let d = match op {
+                    "R" => Ok((1, 0)),
+                    "L" => Ok((-1, 0)),
+                    "U" => Ok((0, 1)),
+                    "D" => Ok((0, -1)),
+                    _ => Err(anyhow!("unmatched up")),
+                }?;
+                Ok((d, n))
+            })
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let moves = parse(input.as_bytes())?;
 
     let mut h = (0, 0);
     let mut t = (0, 0);
     let mut t_locs: HashSet<(i32, i32)> = HashSet::new();
     t_locs.insert(t);
 
-    for line in &lines {
-        let (op, n_str) = line
-            .split_once(' ')
-            .ok_or_else(|| anyhow!("couldn't split"))?;
-        let n = n_str.parse::<i32>()?;
-        let d = match op {
-            "R" => Ok((1, 0)),
-            "L" => Ok((-1, 0)),
-            "U" => Ok((0, 1)),
-            "D" => Ok((0, -1)),
-            _ => Err(anyhow!("unmatched up")),
-        }?;
+    for (d, n) in moves {
         for _ in 0..n {
             let (hx, hy) = h;
             let (tx, ty) = t;
@@ -41,24 +49,17 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", t_locs.len());
+    Ok(Answer::try_from(t_locs.len())?)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let moves = parse(input.as_bytes())?;
 
     let mut knots: Vec<(i32, i32)> = (0..10).map(|_| (0, 0)).collect();
     let mut t_locs: HashSet<(i32, i32)> = HashSet::new();
     t_locs.insert(knots[9]);
 
-    for line in lines {
-        let (op, n_str) = line
-            .split_once(' ')
-            .ok_or_else(|| anyhow!("couldn't split"))?;
-        let n = n_str.parse::<i32>()?;
-        let d = match op {
-            "R" => Ok((1, 0)),
-            "L" => Ok((-1, 0)),
-            "U" => Ok((0, 1)),
-            "D" => Ok((0, -1)),
-            _ => Err(anyhow!("unmatched up")),
-        }?;
+    for (d, n) in moves {
         for _ in 0..n {
             let mut di = d;
             for i in 0..10 {
@@ -94,7 +95,5 @@ fn main() -> Result<()> {
         }
     }
 
-    println!("{}", t_locs.len());
-
-    Ok(())
+    Ok(Answer::try_from(t_locs.len())?)
 }
@@ -0,0 +1,33 @@
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::read_to_string,
+    io::{stdin, Read},
+    path::{Path, PathBuf},
+};
+
+/// Directory searched for `inputN.txt` when no explicit input is given.
+pub const DEFAULT_INPUTS_DIR: &str = ".";
+
+/// The conventional location of `day`'s input inside `inputs_dir`.
+pub fn default_path(inputs_dir: &Path, day: u8) -> PathBuf {
+    inputs_dir.join(format!("input{}.txt", day))
+}
+
+/// Reads puzzle input from `path`, or from stdin if `path` is `-`.
+pub fn read(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut buf = String::new();
+        stdin()
+            .read_to_string(&mut buf)
+            .map_err(|e| anyhow!("couldn't read stdin: {}", e))?;
+        Ok(buf)
+    } else {
+        read_to_string(path).map_err(|e| anyhow!("couldn't read {}: {}", path.display(), e))
+    }
+}
+
+/// A hex SHA-256 digest of `input`, identifying which puzzle input produced an answer.
+pub fn hash(input: &str) -> String {
+    format!("{:x}", Sha256::digest(input.as_bytes()))
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use clap::ValueEnum;
+
+mod answer;
+pub mod answers;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod input;
+pub mod mongo;
+pub mod sqlite;
+
+pub use answer::Answer;
+
+/// What solvers may need to know besides their input.
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    /// Where the input came from, stored on the documents the MongoDB
+    /// solvers load.
+    pub source: String,
+    /// What the offline MongoDB engine behaves as if a server supports, so
+    /// the pipeline variants for older servers can be run without one.
+    pub emulated: mongo::Capabilities,
+    /// The hand game day 2 plays on every backend.
+    pub rules: day2::Rules,
+    /// How day 3 groups rucksacks and prioritizes their items on every
+    /// backend.
+    pub rucksacks: day3::Config,
+}
+
+pub type Solver = fn(&str, &Context) -> Result<Answer>;
+
+/// Adapts a native solver, which only needs its input, to [`Solver`].
+macro_rules! native {
+    ($solver:path) => {
+        (|input, _| $solver(input)) as Solver
+    };
+}
+
+/// Where the solvers do their work, for the days a backend implements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Plain Rust
+    Native,
+    /// Aggregation pipelines on a MongoDB server
+    Mongo,
+    /// The same aggregation pipelines, run in-process without a server
+    MongoOffline,
+    /// SQL queries on an in-memory SQLite database
+    Sqlite,
+}
+
+impl Backend {
+    /// The name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Native => "native",
+            Backend::Mongo => "mongo",
+            Backend::MongoOffline => "mongo-offline",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    /// The backend that actually solves `day`: days without an
+    /// implementation on this backend always use their native solvers.
+    pub fn for_day(self, day: u8) -> Backend {
+        if implementation(self, day).is_some() {
+            self
+        } else {
+            Backend::Native
+        }
+    }
+}
+
+/// A day's part 1 and part 2 solvers, for backends that only implement some
+/// days.
+pub type Implementation = (u8, Solver, Solver);
+
+fn implementation(backend: Backend, day: u8) -> Option<(Solver, Option<Solver>)> {
+    let days: &[Implementation] = match backend {
+        Backend::Native => return DAYS.get(usize::from(day).checked_sub(1)?).copied(),
+        Backend::Mongo => &mongo::DAYS,
+        Backend::MongoOffline => &mongo::OFFLINE_DAYS,
+        Backend::Sqlite => &sqlite::DAYS,
+    };
+    days.iter()
+        .find(|(d, _, _)| *d == day)
+        .map(|&(_, part1, part2)| (part1, Some(part2)))
+}
+
+/// The solver for `day`'s `part` on `backend`, if the day has that part.
+pub fn solver(day: u8, part: u8, backend: Backend) -> Option<Solver> {
+    let (part1, part2) = implementation(backend.for_day(day), day)?;
+    if part == 1 {
+        Some(part1)
+    } else {
+        part2
+    }
+}
+
+/// The native part 1 and part 2 solvers for each day, indexed by `day - 1`.
+/// Day 25 has no part 2.
+pub const DAYS: [(Solver, Option<Solver>); 25] = [
+    (native!(day1::part1), Some(native!(day1::part2))),
+    (
+        |input, context| day2::part1(input, &context.rules),
+        Some(|input, context| day2::part2(input, &context.rules)),
+    ),
+    (
+        |input, context| day3::part1(input, &context.rucksacks),
+        Some(|input, context| day3::part2(input, &context.rucksacks)),
+    ),
+    (native!(day4::part1), Some(native!(day4::part2))),
+    (native!(day5::part1), Some(native!(day5::part2))),
+    (native!(day6::part1), Some(native!(day6::part2))),
+    (native!(day7::part1), Some(native!(day7::part2))),
+    (native!(day8::part1), Some(native!(day8::part2))),
+    (native!(day9::part1), Some(native!(day9::part2))),
+    (native!(day10::part1), Some(native!(day10::part2))),
+    (native!(day11::part1), Some(native!(day11::part2))),
+    (native!(day12::part1), Some(native!(day12::part2))),
+    (native!(day13::part1), Some(native!(day13::part2))),
+    (native!(day14::part1), Some(native!(day14::part2))),
+    (native!(day15::part1), Some(native!(day15::part2))),
+    (native!(day16::part1), Some(native!(day16::part2))),
+    (native!(day17::part1), Some(native!(day17::part2))),
+    (native!(day18::part1), Some(native!(day18::part2))),
+    (native!(day19::part1), Some(native!(day19::part2))),
+    (native!(day20::part1), Some(native!(day20::part2))),
+    (native!(day21::part1), Some(native!(day21::part2))),
+    (native!(day22::part1), Some(native!(day22::part2))),
+    (native!(day23::part1), Some(native!(day23::part2))),
+    (native!(day24::part1), Some(native!(day24::part2))),
+    (native!(day25::part1), None),
+];
@@ -0,0 +1,816 @@
+use anyhow::{anyhow, bail, Result};
+use aoc_2022::{
+    answers::{self, Answers, Status},
+    day2::{self, Analysis, Rules},
+    day3, input,
+    mongo::{
+        self, capabilities,
+        elves::{self, Elf},
+        export::{self, Export},
+        results, Capabilities,
+    },
+    solver, Answer, Backend, Context, Solver,
+};
+use clap::{value_parser, Parser, Subcommand, ValueEnum};
+use mongodb::bson::DateTime;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code 2022 solutions")]
+struct Cli {
+    /// Directory containing inputN.txt files, used when --input isn't given
+    #[arg(long, global = true, env = "AOC_INPUTS_DIR", default_value = input::DEFAULT_INPUTS_DIR)]
+    inputs_dir: PathBuf,
+    /// File of known answers used by verify and written by run --record
+    #[arg(long, global = true, env = "AOC_ANSWERS", default_value = answers::DEFAULT_ANSWERS_PATH)]
+    answers: PathBuf,
+    /// How to print results
+    #[arg(long, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    /// Where to solve the days a backend implements, with the rest solved
+    /// natively; defaults to mongo if a server is reachable and native
+    /// otherwise
+    #[arg(long, global = true, value_enum, env = "AOC_BACKEND")]
+    backend: Option<Backend>,
+    /// Rules file for the hand game day 2 plays on every backend, such as
+    /// rules/rock-paper-scissors-lizard-spock.toml; rock-paper-scissors if
+    /// omitted
+    #[arg(long, global = true, env = "AOC_DAY2_RULES")]
+    day2_rules: Option<PathBuf>,
+    /// Rucksacks per group in day 3 part 2
+    #[arg(long, global = true, env = "AOC_DAY3_GROUP_SIZE", default_value_t = 3)]
+    day3_group_size: usize,
+    /// Day 3's items in priority order, lowest first
+    #[arg(long, global = true, env = "AOC_DAY3_ALPHABET", default_value = day3::DEFAULT_ALPHABET)]
+    day3_alphabet: String,
+    /// MongoDB connection string, including any credentials and TLS options
+    #[arg(
+        long,
+        global = true,
+        env = "AOC_MONGO_URI",
+        hide_env_values = true,
+        default_value = mongo::DEFAULT_URI
+    )]
+    mongo_uri: String,
+    /// MongoDB database to load inputs into
+    #[arg(long, global = true, env = "AOC_MONGO_DB", default_value = mongo::DEFAULT_DATABASE)]
+    mongo_db: String,
+    /// Prefix of the collections each day's input is loaded into
+    #[arg(
+        long,
+        global = true,
+        env = "AOC_MONGO_COLLECTION_PREFIX",
+        default_value = mongo::DEFAULT_COLLECTION_PREFIX
+    )]
+    mongo_collection_prefix: String,
+    /// Leave loaded inputs in MongoDB instead of dropping them after each run,
+    /// in the collection named after the input's hash that --mongo-reuse
+    /// uses, and print its name
+    #[arg(long, global = true, env = "AOC_MONGO_KEEP_DATA")]
+    keep_data: bool,
+    /// Keep each loaded input in a collection named after its hash, and reuse
+    /// it when the same input is run again instead of reloading it
+    #[arg(long, global = true, env = "AOC_MONGO_REUSE")]
+    mongo_reuse: bool,
+    /// Most documents to insert into MongoDB in one batch
+    #[arg(long, global = true, env = "AOC_MONGO_BATCH_SIZE", default_value_t = mongo::DEFAULT_BATCH_SIZE)]
+    mongo_batch_size: usize,
+    /// Insert batches concurrently and unordered; pipelines sort on line numbers
+    /// so answers are unaffected
+    #[arg(long, global = true, env = "AOC_MONGO_UNORDERED")]
+    mongo_unordered: bool,
+    /// Explain each aggregation and print how long each stage took, how many
+    /// documents went in and out, and whether it spilled to disk. On a server
+    /// this runs each pipeline a second time, which the timings include, and
+    /// pipelines that write with $out or $merge aren't explained
+    #[arg(long, global = true, env = "AOC_MONGO_EXPLAIN")]
+    explain: bool,
+    /// Let aggregation stages spill to disk; without it the server's default
+    /// applies
+    #[arg(long, global = true, env = "AOC_MONGO_ALLOW_DISK_USE")]
+    allow_disk_use: bool,
+    /// Also write every run to the MongoDB results collection, whichever
+    /// backend solved it
+    #[arg(long, global = true, env = "AOC_MONGO_RESULTS")]
+    results: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run solvers and print a table of answers and timings
+    Run {
+        /// Day to run
+        #[arg(long, value_parser = value_parser!(u8).range(1..=25), required_unless_present = "all")]
+        day: Option<u8>,
+        /// Part to run; both parts are run if omitted
+        #[arg(long, value_parser = value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+        /// Run every day
+        #[arg(long, conflicts_with = "day")]
+        all: bool,
+        /// Input file to use instead of the inputs directory, or - for stdin
+        #[arg(long, conflicts_with = "all")]
+        input: Option<PathBuf>,
+        /// Store every answer produced in the answers file
+        #[arg(long)]
+        record: bool,
+    },
+    /// Run solvers and compare their answers to the answers file, failing on
+    /// any regression. Parts the answers file skips are only run when both
+    /// --day and --part name them
+    Verify {
+        /// Day to verify; every day is verified if omitted
+        #[arg(long, value_parser = value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+        /// Part to verify; both parts are verified if omitted
+        #[arg(long, value_parser = value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+    },
+    /// List the available days and their input files
+    List,
+    /// Write mongosh scripts that load a day's input and run its pipelines
+    Export {
+        /// Day to export; every day with pipelines is exported if omitted
+        #[arg(long, value_parser = value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+        /// Input file to use instead of the inputs directory
+        #[arg(long, requires = "day")]
+        input: Option<PathBuf>,
+        /// Directory to write dayN.js files to
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+        /// Server version to pick pipeline variants for, such as 4.4; the newest
+        /// variants are used if omitted
+        #[arg(long, value_parser = server_version)]
+        server_version: Option<(u32, u32)>,
+        /// Avoid pipelines that need server-side JavaScript
+        #[arg(long)]
+        no_javascript: bool,
+    },
+    /// Show the runs of a day stored by --results, oldest first, marking
+    /// answers that changed since the previous run of their part on the same
+    /// input
+    History {
+        /// Day to show
+        #[arg(long, value_parser = value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Part to show; both parts are shown if omitted
+        #[arg(long, value_parser = value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+        /// Most recent runs to show
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// Write day 1's per-elf item counts, calorie totals, ranks and
+    /// percentiles to the MongoDB elves collection with $merge, and print the
+    /// top elves
+    ElfReport {
+        /// Input file to use instead of the inputs directory
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Number of top elves to print
+        #[arg(long, default_value_t = 3)]
+        top: i64,
+    },
+    /// Find the reading of day 2's strategy symbols as shapes that scores the
+    /// most against the input's opponent, showing how often the opponent
+    /// played each shape and the score of every reading, for games of up to
+    /// 9 shapes
+    Strategy {
+        /// Input file to use instead of the inputs directory
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+    /// Query the elves collection written by elf-report for an input's elves,
+    /// best first
+    Elves {
+        /// Input file whose report to query instead of the inputs directory's
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Only elves carrying more calories than this
+        #[arg(long)]
+        more_than: Option<i64>,
+        /// Only the elves ranked this or better
+        #[arg(long)]
+        top: Option<i64>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// An aligned table with a total time
+    Table,
+    /// One JSON object per line for each part run
+    Json,
+}
+
+struct Run {
+    day: u8,
+    part: u8,
+    backend: Backend,
+    timestamp: DateTime,
+    elapsed: Duration,
+    input_hash: Option<String>,
+    result: Result<Answer>,
+}
+
+fn server_version(version: &str) -> Result<(u32, u32)> {
+    capabilities::parse_version(version)
+        .ok_or_else(|| anyhow!("expected a version such as 5.0, got {}", version))
+}
+
+fn backend(requested: Option<Backend>, days: &[u8]) -> Backend {
+    requested.unwrap_or_else(|| {
+        if !days
+            .iter()
+            .any(|&d| Backend::Mongo.for_day(d) == Backend::Mongo)
+        {
+            Backend::Native
+        } else if mongo::available() {
+            Backend::Mongo
+        } else {
+            eprintln!("MongoDB isn't reachable, using the native backend");
+            Backend::Native
+        }
+    })
+}
+
+fn run(
+    day: u8,
+    part: u8,
+    solver: Solver,
+    input: &Result<String>,
+    context: &Context,
+    backend: Backend,
+) -> Run {
+    let timestamp = DateTime::now();
+    let start = Instant::now();
+    let result = match input {
+        Ok(input) => solver(input, context),
+        Err(e) => Err(anyhow!("{}", e)),
+    };
+    Run {
+        day,
+        part,
+        backend: backend.for_day(day),
+        timestamp,
+        elapsed: start.elapsed(),
+        input_hash: input.as_ref().ok().map(|i| input::hash(i)),
+        result,
+    }
+}
+
+/// The commit checked out in the source tree, if it's a git repository.
+fn git_commit() -> Option<String> {
+    let output = process::Command::new("git")
+        .args(["-C", env!("CARGO_MANIFEST_DIR"), "rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn save_results(runs: &[Run]) -> Result<()> {
+    let commit = git_commit();
+    let records: Vec<_> = runs
+        .iter()
+        .map(|run| results::Record {
+            day: run.day,
+            part: run.part,
+            result: run.result.as_ref().cloned().map_err(|e| e.to_string()),
+            backend: run.backend,
+            elapsed: run.elapsed,
+            input_hash: run.input_hash.clone(),
+            commit: commit.clone(),
+            timestamp: run.timestamp,
+        })
+        .collect();
+    results::save(&records)
+}
+
+/// Numbers stay numbers, and multi-line text such as day 10's CRT image
+/// becomes an array of lines.
+fn answer_json(answer: &Answer) -> (&'static str, Value) {
+    match answer {
+        Answer::Num(n) => ("number", json!(n)),
+        Answer::Text(s) if s.contains('\n') => ("image", json!(s.lines().collect::<Vec<_>>())),
+        Answer::Text(s) => ("string", json!(s)),
+    }
+}
+
+fn print_json(run: &Run, status: Option<&Status>) {
+    let mut record = json!({
+        "day": run.day,
+        "part": run.part,
+        "elapsed_secs": run.elapsed.as_secs_f64(),
+        "input_hash": run.input_hash,
+    });
+    match &run.result {
+        Ok(answer) => {
+            let (kind, answer) = answer_json(answer);
+            record["kind"] = json!(kind);
+            record["answer"] = answer;
+        }
+        Err(e) => record["error"] = json!(e.to_string()),
+    }
+    if let Some(status) = status {
+        let (label, expected) = status_label(status);
+        record["status"] = json!(label);
+        if let Some(expected) = expected {
+            record["expected"] = answer_json(expected).1;
+        }
+    }
+    println!("{}", record);
+}
+
+fn print_header() {
+    println!("{:>3}  {:>4}  {:>12}  answer", "day", "part", "time");
+}
+
+fn print_row(run: &Run) {
+    let answer = match &run.result {
+        Ok(answer) => answer.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    let mut lines = answer.lines();
+    println!(
+        "{:>3}  {:>4}  {:>12}  {}",
+        run.day,
+        run.part,
+        format!("{:.3?}", run.elapsed),
+        lines.next().unwrap_or_default()
+    );
+    for line in lines {
+        println!("{:>25}{}", "", line);
+    }
+}
+
+fn print_status_header() {
+    println!(
+        "{:>3}  {:>4}  {:>12}  {:<8}  answer",
+        "day", "part", "time", "status"
+    );
+}
+
+fn status_label(status: &Status) -> (&'static str, Option<&Answer>) {
+    match status {
+        Status::Ok => ("ok", None),
+        Status::Mismatch(expected) => ("mismatch", Some(expected)),
+        Status::Failed(expected) => ("failed", Some(expected)),
+        Status::New => ("new", None),
+        Status::Missing => ("missing", None),
+    }
+}
+
+fn print_status_row(run: &Run, status: &Status) {
+    let (label, expected) = status_label(status);
+    let label = if status.is_regression() {
+        label.to_uppercase()
+    } else {
+        label.to_string()
+    };
+    let mut answer = match &run.result {
+        Ok(answer) => answer.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    if let Some(expected) = expected {
+        answer = format!("{}\nexpected: {}", answer, expected);
+    }
+    let mut lines = answer.lines();
+    println!(
+        "{:>3}  {:>4}  {:>12}  {:<8}  {}",
+        run.day,
+        run.part,
+        format!("{:.3?}", run.elapsed),
+        label,
+        lines.next().unwrap_or_default()
+    );
+    for line in lines {
+        println!("{:>35}{}", "", line);
+    }
+}
+
+fn print_skipped_row(day: u8, part: u8) {
+    println!("{:>3}  {:>4}  {:>12}  skipped", day, part, "");
+}
+
+fn timestamp(timestamp: DateTime) -> String {
+    timestamp
+        .try_to_rfc3339_string()
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn print_history_json(record: &results::Record, changed: bool) {
+    let mut json = json!({
+        "day": record.day,
+        "part": record.part,
+        "timestamp": timestamp(record.timestamp),
+        "backend": record.backend.name(),
+        "elapsed_secs": record.elapsed.as_secs_f64(),
+        "input_hash": record.input_hash,
+        "commit": record.commit,
+        "changed": changed,
+    });
+    match &record.result {
+        Ok(answer) => {
+            let (kind, answer) = answer_json(answer);
+            json["kind"] = json!(kind);
+            json["answer"] = answer;
+        }
+        Err(e) => json["error"] = json!(e),
+    }
+    println!("{}", json);
+}
+
+fn print_history_header() {
+    println!(
+        "{:<24}  {:>4}  {:<13}  {:>12}  {:<8}  {:<7}  answer",
+        "timestamp", "part", "backend", "time", "commit", "changed"
+    );
+}
+
+fn print_history_row(record: &results::Record, changed: bool) {
+    let answer = match &record.result {
+        Ok(answer) => answer.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    let mut lines = answer.lines();
+    println!(
+        "{:<24}  {:>4}  {:<13}  {:>12}  {:<8}  {:<7}  {}",
+        timestamp(record.timestamp),
+        record.part,
+        record.backend.name(),
+        format!("{:.3?}", record.elapsed),
+        record
+            .commit
+            .as_deref()
+            .map_or("", |c| &c[..c.len().min(8)]),
+        if changed { "yes" } else { "" },
+        lines.next().unwrap_or_default()
+    );
+    for line in lines {
+        println!("{:>80}{}", "", line);
+    }
+}
+
+fn print_elves(elves: &[Elf], format: Format) {
+    if format == Format::Table {
+        println!(
+            "{:>4}  {:>5}  {:>8}  {:>4}  {:>10}",
+            "elf", "items", "calories", "rank", "percentile"
+        );
+    }
+    for elf in elves {
+        match format {
+            Format::Table => println!(
+                "{:>4}  {:>5}  {:>8}  {:>4}  {:>10.1}",
+                elf.elf.0, elf.items.0, elf.total_calories.0, elf.rank.0, elf.percentile
+            ),
+            Format::Json => println!(
+                "{}",
+                json!({
+                    "elf": elf.elf.0,
+                    "items": elf.items.0,
+                    "total_calories": elf.total_calories.0,
+                    "rank": elf.rank.0,
+                    "percentile": elf.percentile,
+                })
+            ),
+        }
+    }
+}
+
+fn print_analysis(analysis: &Analysis, rules: &Rules, format: Format) {
+    let rounds = analysis.rounds().max(1) as f64;
+    let mapping = |shapes: &[usize]| -> Vec<(&str, &str)> {
+        rules
+            .shapes
+            .iter()
+            .zip(shapes)
+            .map(|(symbol, &shape)| (symbol.player.as_str(), rules.shapes[shape].name.as_str()))
+            .collect()
+    };
+    if format == Format::Json {
+        let opponent: serde_json::Map<String, Value> = rules
+            .shapes
+            .iter()
+            .zip(&analysis.opponent)
+            .map(|(shape, &count)| (shape.name.clone(), json!(count)))
+            .collect();
+        let mappings: Vec<Value> = analysis
+            .mappings
+            .iter()
+            .map(|m| {
+                json!({
+                    "mapping": mapping(&m.shapes)
+                        .into_iter()
+                        .map(|(symbol, shape)| (symbol.to_string(), json!(shape)))
+                        .collect::<serde_json::Map<String, Value>>(),
+                    "total": m.total,
+                    "expected": m.total as f64 / rounds,
+                })
+            })
+            .collect();
+        println!("{}", json!({ "opponent": opponent, "mappings": mappings }));
+        return;
+    }
+
+    println!("{:<10}  {:>6}  {:>6}", "opponent", "rounds", "share");
+    for (shape, &count) in rules.shapes.iter().zip(&analysis.opponent) {
+        println!(
+            "{:<10}  {:>6}  {:>5.1}%",
+            shape.name,
+            count,
+            count as f64 * 100.0 / rounds
+        );
+    }
+    println!();
+    println!("{:>8}  {:>8}  mapping", "total", "expected");
+    for (i, m) in analysis.mappings.iter().enumerate() {
+        let shapes: Vec<String> = mapping(&m.shapes)
+            .into_iter()
+            .map(|(symbol, shape)| format!("{}={}", symbol, shape))
+            .collect();
+        println!(
+            "{:>8}  {:>8.3}  {}{}",
+            m.total,
+            m.total as f64 / rounds,
+            shapes.join(" "),
+            if i == 0 { "  (best)" } else { "" }
+        );
+    }
+}
+
+fn print_total(runs: &[Run]) {
+    let total: Duration = runs.iter().map(|r| r.elapsed).sum();
+    println!("{:>3}  {:>4}  {:>12}", "", "", format!("{:.3?}", total));
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    mongo::configure(mongo::Config {
+        uri: cli.mongo_uri.clone(),
+        database: cli.mongo_db.clone(),
+        collection_prefix: cli.mongo_collection_prefix.clone(),
+        keep_data: cli.keep_data,
+        reuse: cli.mongo_reuse,
+        batch_size: cli.mongo_batch_size,
+        ordered: !cli.mongo_unordered,
+        explain: cli.explain,
+        allow_disk_use: cli.allow_disk_use.then_some(true),
+    })?;
+    // Runs add where their input came from.
+    let context = Context {
+        rules: match &cli.day2_rules {
+            Some(path) => Rules::load(path)?,
+            None => Rules::default(),
+        },
+        rucksacks: day3::Config::new(cli.day3_group_size, cli.day3_alphabet.clone())?,
+        ..Context::default()
+    };
+
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            all,
+            input,
+            record,
+        } => {
+            let days: Vec<u8> = if all {
+                (1..=25).collect()
+            } else {
+                vec![day.ok_or_else(|| anyhow!("--day or --all is required"))?]
+            };
+            let backend = backend(cli.backend, &days);
+            let parts = part.map_or_else(|| vec![1, 2], |p| vec![p]);
+            if let (Some(day), Some(part)) = (day, part) {
+                if solver(day, part, backend).is_none() {
+                    bail!("day {} has no part {}", day, part);
+                }
+            }
+            if cli.format == Format::Table {
+                print_header();
+            }
+            let mut runs = vec![];
+            for day in days {
+                let path = input
+                    .clone()
+                    .unwrap_or_else(|| input::default_path(&cli.inputs_dir, day));
+                let input = input::read(&path);
+                let context = Context {
+                    source: path.display().to_string(),
+                    ..context.clone()
+                };
+                for &part in &parts {
+                    if let Some(solver) = solver(day, part, backend) {
+                        let run = run(day, part, solver, &input, &context, backend);
+                        match cli.format {
+                            Format::Table => print_row(&run),
+                            Format::Json => print_json(&run, None),
+                        }
+                        runs.push(run);
+                    }
+                }
+            }
+            if cli.format == Format::Table {
+                print_total(&runs);
+            }
+            if cli.results {
+                save_results(&runs)?;
+            }
+            if record {
+                let mut answers = Answers::load(&cli.answers)?;
+                for run in &runs {
+                    if let Ok(answer) = &run.result {
+                        answers.insert(run.day, run.part, answer.clone());
+                    }
+                }
+                answers.save(&cli.answers)?;
+            }
+            let failed = runs.iter().filter(|r| r.result.is_err()).count();
+            if !all {
+                if let Some(Run { result: Err(e), .. }) =
+                    runs.into_iter().find(|r| r.result.is_err())
+                {
+                    return Err(e);
+                }
+            } else if failed > 0 {
+                return Err(anyhow!("{} part(s) failed", failed));
+            }
+        }
+        Command::Verify { day, part } => {
+            let answers = Answers::load(&cli.answers)?;
+            let days: Vec<u8> = day.map_or_else(|| (1..=25).collect(), |d| vec![d]);
+            let backend = backend(cli.backend, &days);
+            let parts = part.map_or_else(|| vec![1, 2], |p| vec![p]);
+            if let (Some(day), Some(part)) = (day, part) {
+                if solver(day, part, backend).is_none() {
+                    bail!("day {} has no part {}", day, part);
+                }
+            }
+            // Parts the answers file skips are only run when named exactly.
+            let named = day.is_some() && part.is_some();
+            if cli.format == Format::Table {
+                print_status_header();
+            }
+            let mut runs = vec![];
+            let mut regressions = 0;
+            for day in days {
+                let path = input::default_path(&cli.inputs_dir, day);
+                let input = input::read(&path);
+                let context = Context {
+                    source: path.display().to_string(),
+                    ..context.clone()
+                };
+                for &part in &parts {
+                    let solver = match solver(day, part, backend) {
+                        Some(solver) => solver,
+                        None => continue,
+                    };
+                    if answers.is_skipped(day, part) && !named {
+                        match cli.format {
+                            Format::Table => print_skipped_row(day, part),
+                            Format::Json => {
+                                println!(
+                                    "{}",
+                                    json!({ "day": day, "part": part, "status": "skipped" })
+                                )
+                            }
+                        }
+                        continue;
+                    }
+                    let run = run(day, part, solver, &input, &context, backend);
+                    let status = answers.check(day, part, &run.result);
+                    match cli.format {
+                        Format::Table => print_status_row(&run, &status),
+                        Format::Json => print_json(&run, Some(&status)),
+                    }
+                    if status.is_regression() {
+                        regressions += 1;
+                    }
+                    runs.push(run);
+                }
+            }
+            if cli.format == Format::Table {
+                print_total(&runs);
+            }
+            if cli.results {
+                save_results(&runs)?;
+            }
+            if regressions > 0 {
+                return Err(anyhow!("{} part(s) regressed", regressions));
+            }
+        }
+        Command::List => {
+            for day in 1..=25 {
+                let path = input::default_path(&cli.inputs_dir, day);
+                println!(
+                    "day{:<2}  {}{}",
+                    day,
+                    path.display(),
+                    if path.exists() { "" } else { " (missing)" }
+                );
+            }
+        }
+        Command::Export {
+            day,
+            input,
+            out_dir,
+            server_version,
+            no_javascript,
+        } => {
+            let capabilities = match server_version {
+                Some(version) => Capabilities::for_version(version, !no_javascript)?,
+                None => Capabilities {
+                    javascript: !no_javascript,
+                    ..Capabilities::ALL
+                },
+            };
+            let days: Vec<u8> = day.map_or_else(|| export::days().collect(), |d| vec![d]);
+            fs::create_dir_all(&out_dir)?;
+            for day in days {
+                let path = input
+                    .clone()
+                    .unwrap_or_else(|| input::default_path(&cli.inputs_dir, day));
+                let context = Context {
+                    source: path.display().to_string(),
+                    ..context.clone()
+                };
+                let export = Export::new(day, &input::read(&path)?, &context, &capabilities)?;
+                let collection = format!("{}{}", cli.mongo_collection_prefix, day);
+                let script_path = out_dir.join(format!("day{}.js", day));
+                fs::write(&script_path, export.script(&collection)?)?;
+                println!("{}", script_path.display());
+            }
+        }
+        Command::ElfReport { input, top } => {
+            let path = input.unwrap_or_else(|| input::default_path(&cli.inputs_dir, 1));
+            let input = input::read(&path)?;
+            let context = Context {
+                source: path.display().to_string(),
+                ..context.clone()
+            };
+            elves::report(&input, &context)?;
+            let query = elves::Query {
+                top: Some(top),
+                ..Default::default()
+            };
+            print_elves(&elves::query(&input, &query)?, cli.format);
+        }
+        Command::Strategy { input } => {
+            let path = input.unwrap_or_else(|| input::default_path(&cli.inputs_dir, 2));
+            let input = input::read(&path)?;
+            let context = Context {
+                source: path.display().to_string(),
+                ..context.clone()
+            };
+            let analysis = match backend(cli.backend, &[2]) {
+                Backend::Mongo => mongo::day2::analyze::<mongo::Server>(&input, &context)?,
+                Backend::MongoOffline => mongo::day2::analyze::<mongo::Offline>(&input, &context)?,
+                Backend::Native | Backend::Sqlite => day2::analyze(&input, &context.rules)?,
+            };
+            print_analysis(&analysis, &context.rules, cli.format);
+        }
+        Command::Elves {
+            input,
+            more_than,
+            top,
+        } => {
+            let path = input.unwrap_or_else(|| input::default_path(&cli.inputs_dir, 1));
+            let query = elves::Query { more_than, top };
+            print_elves(&elves::query(&input::read(&path)?, &query)?, cli.format);
+        }
+        Command::History { day, part, limit } => {
+            let records = results::history(day, part, limit)?;
+            if cli.format == Format::Table {
+                print_history_header();
+            }
+            // An answer changed if it differs from the previous run of its part
+            // on the same input.
+            let mut previous: HashMap<(u8, Option<&str>), &Result<Answer, String>> = HashMap::new();
+            for record in &records {
+                let changed = previous
+                    .insert((record.part, record.input_hash.as_deref()), &record.result)
+                    .is_some_and(|last| *last != record.result);
+                match cli.format {
+                    Format::Table => print_history_row(record, changed),
+                    Format::Json => print_history_json(record, changed),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::{bail, Result};
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Database,
+};
+
+/// The oldest server the pipelines have a variant for; `$first` and
+/// `$function` need 4.4.
+pub const MIN_VERSION: (u32, u32) = (4, 4);
+
+/// The optional features the pipelines pick variants around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `$setWindowFields`, from 5.0.
+    pub window_fields: bool,
+    /// The `$topN` accumulator, from 5.2.
+    pub top_n: bool,
+    /// `$function` with `lang: "js"`, unless server-side JavaScript is off.
+    pub javascript: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::ALL
+    }
+}
+
+impl Capabilities {
+    /// Everything the pipelines can use.
+    pub const ALL: Capabilities = Capabilities {
+        window_fields: true,
+        top_n: true,
+        javascript: true,
+    };
+
+    /// What a server with the given feature compatibility version has.
+    pub fn for_version(version: (u32, u32), javascript: bool) -> Result<Self> {
+        if version < MIN_VERSION {
+            bail!(
+                "MongoDB {}.{} is too old; the pipelines need {}.{} or newer",
+                version.0,
+                version.1,
+                MIN_VERSION.0,
+                MIN_VERSION.1
+            );
+        }
+        Ok(Capabilities {
+            window_fields: version >= (5, 0),
+            top_n: version >= (5, 2),
+            javascript,
+        })
+    }
+
+    /// Asks the server behind `admin` what it supports.
+    ///
+    /// The usable version is the lower of the binary version from `buildInfo`
+    /// and the `featureCompatibilityVersion` parameter, since new operators are
+    /// rejected until the FCV is raised. JavaScript is off if the build has no
+    /// engine or `security.javascriptEnabled` is false. The parameter and
+    /// command-line queries need privileges that hosted deployments may not
+    /// grant, so failures there fall back to what `buildInfo` says.
+    pub async fn detect(admin: &Database) -> Result<Self> {
+        let build_info = admin.run_command(doc! { "buildInfo": 1 }, None).await?;
+        let mut version = version_of(build_info.get("versionArray"))
+            .or_else(|| parse_version(build_info.get_str("version").ok()?))
+            .unwrap_or((0, 0));
+
+        if let Ok(parameters) = admin
+            .run_command(
+                doc! { "getParameter": 1, "featureCompatibilityVersion": 1 },
+                None,
+            )
+            .await
+        {
+            if let Some(fcv) = parameters
+                .get_document("featureCompatibilityVersion")
+                .ok()
+                .and_then(|fcv| fcv.get_str("version").ok())
+                .and_then(parse_version)
+            {
+                version = version.min(fcv);
+            }
+        }
+
+        let mut javascript = build_info.get_str("javascriptEngine") != Ok("none");
+        if let Ok(options) = admin.run_command(doc! { "getCmdLineOpts": 1 }, None).await {
+            let enabled = options
+                .get_document("parsed")
+                .and_then(|parsed| parsed.get_document("security"))
+                .and_then(|security: &Document| security.get_bool("javascriptEnabled"));
+            if enabled == Ok(false) {
+                javascript = false;
+            }
+        }
+
+        Capabilities::for_version(version, javascript)
+    }
+}
+
+fn version_of(array: Option<&Bson>) -> Option<(u32, u32)> {
+    let parts = array?.as_array()?;
+    let part = |i: usize| match parts.get(i)? {
+        Bson::Int32(n) => u32::try_from(*n).ok(),
+        Bson::Int64(n) => u32::try_from(*n).ok(),
+        _ => None,
+    };
+    Some((part(0)?, part(1)?))
+}
+
+/// The major and minor version in a version string such as `6.0.3`.
+pub fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
@@ -0,0 +1,224 @@
+use anyhow::{Error, Result};
+use mongodb::bson::{doc, Bson, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new)
+                .map(|c| c.parse().ok())
+                .map(|o: Option<i32>| {
+                    if let Some(n) = o {
+                        doc! { "calories": n }
+                    } else {
+                        doc! {}
+                    }
+                })
+        })
+        .collect()
+}
+
+/// One document per elf with its 1-based `elf` number, its number of
+/// `items` and their `totalCalories`.
+fn elf_totals(capabilities: &Capabilities) -> Pipeline {
+    if capabilities.window_fields {
+        return Pipeline::new()
+            .set_window_fields(
+                SetWindowFields::new()
+                    .partition_by(0)
+                    .sort_by(&[("line", 1)])
+                    .output(
+                        "elfNum",
+                        Window::sum(cond(lt(field("calories"), Bson::Null), 1, 0))
+                            .documents(Bound::Unbounded, Bound::Current),
+                    ),
+            )
+            .match_expr(field("calories"))
+            .group(
+                Group::by(field("elfNum"))
+                    .sum("items", 1)
+                    .sum("totalCalories", field("calories")),
+            )
+            .project([
+                ("elf", add([field("_id"), 1.into()])),
+                ("items", field("items")),
+                ("totalCalories", field("totalCalories")),
+            ]);
+    }
+
+    // Without window functions, fold over every line in order, closing off an
+    // elf's total at each blank line.
+    let elf = |items, total| object([("items", items), ("totalCalories", total)]);
+    let state = |elves, current| object([("elves", elves), ("current", current)]);
+    Pipeline::new()
+        .sort(&[("line", 1)])
+        .group(Group::by(Bson::Null).push("lines", var("ROOT")))
+        .project([(
+            "elves",
+            reduce(
+                field("lines"),
+                state(array::<Expr>([]), elf(0.into(), 0.into())),
+                cond(
+                    lt(var("this.calories"), Bson::Null),
+                    state(
+                        concat_arrays([var("value.elves"), array([var("value.current")])]),
+                        elf(0.into(), 0.into()),
+                    ),
+                    state(
+                        var("value.elves"),
+                        elf(
+                            add([var("value.current.items"), 1.into()]),
+                            add([var("value.current.totalCalories"), var("this.calories")]),
+                        ),
+                    ),
+                ),
+            ),
+        )])
+        .project([(
+            "elves",
+            concat_arrays([field("elves.elves"), array([field("elves.current")])]),
+        )])
+        .unwind_with_index("elves", "index")
+        .project([
+            ("elf", add([field("index"), 1.into()])),
+            ("items", field("elves.items")),
+            ("totalCalories", field("elves.totalCalories")),
+        ])
+}
+
+/// Collects the `n` largest totals into the array `field`.
+fn top(pipeline: Pipeline, capabilities: &Capabilities, field_: &str, n: i32) -> Pipeline {
+    if capabilities.top_n {
+        pipeline.group(Group::by(0).top_n(
+            field_,
+            field("totalCalories"),
+            &[("totalCalories", -1)],
+            n,
+        ))
+    } else {
+        pipeline
+            .sort(&[("totalCalories", -1)])
+            .limit(n.into())
+            .group(Group::by(0).push(field_, field("totalCalories")))
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TopElf {
+    top_elf_calories: Integer,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TopThreeElves {
+    top_three_elf_calories: Integer,
+}
+
+pub fn part1_pipeline(capabilities: &Capabilities) -> Pipeline {
+    top(elf_totals(capabilities), capabilities, "topElfCalories", 1)
+        .project([("topElfCalories", array_elem_at(field("topElfCalories"), 0))])
+}
+
+pub fn part2_pipeline(capabilities: &Capabilities) -> Pipeline {
+    top(
+        elf_totals(capabilities),
+        capabilities,
+        "topThreeElfCalories",
+        3,
+    )
+    .project([("topThreeElfCalories", sum(field("topThreeElfCalories")))])
+}
+
+/// Every elf's `elf` number, `items`, `totalCalories`, `rank` by calories and
+/// `percentile`, the percentage of elves carrying no more than it. Each is
+/// keyed by `input_hash` and its number so reports of different inputs can
+/// share a collection.
+pub fn report_pipeline(capabilities: &Capabilities, input_hash: &str) -> Pipeline {
+    let ranked = if capabilities.window_fields {
+        elf_totals(capabilities).set_window_fields(
+            SetWindowFields::new()
+                .sort_by(&[("totalCalories", -1)])
+                .output("rank", Window::rank())
+                .output("elves", Window::count()),
+        )
+    } else {
+        // The first position of an elf's total among every total sorted in
+        // descending order is its rank.
+        elf_totals(capabilities)
+            .sort(&[("totalCalories", -1)])
+            .group(Group::by(Bson::Null).push("elves", var("ROOT")))
+            .project([
+                ("elves", field("elves")),
+                ("totals", map(field("elves"), var("this.totalCalories"))),
+            ])
+            .unwind("elves")
+            .project([
+                ("elf", field("elves.elf")),
+                ("items", field("elves.items")),
+                ("totalCalories", field("elves.totalCalories")),
+                (
+                    "rank",
+                    add([
+                        index_of_array(field("totals"), field("elves.totalCalories")),
+                        1.into(),
+                    ]),
+                ),
+                ("elves", size(field("totals"))),
+            ])
+    };
+    ranked.project([
+        (
+            "_id",
+            object([("inputHash", input_hash.into()), ("elf", field("elf"))]),
+        ),
+        ("inputHash", input_hash.into()),
+        ("elf", field("elf")),
+        ("items", field("items")),
+        ("totalCalories", field("totalCalories")),
+        ("rank", field("rank")),
+        (
+            "percentile",
+            divide(
+                multiply([
+                    100.into(),
+                    subtract(add([field("elves"), 1.into()]), field("rank")),
+                ]),
+                field("elves"),
+            ),
+        ),
+    ])
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part1_pipeline(&E::capabilities(context)?);
+    let TopElf { top_elf_calories } = mongo::single(&E::aggregate(
+        1,
+        input,
+        context,
+        parse(input.as_bytes())?,
+        pipeline.into(),
+    )?)?;
+    Ok(top_elf_calories.into())
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part2_pipeline(&E::capabilities(context)?);
+    let TopThreeElves {
+        top_three_elf_calories,
+    } = mongo::single(&E::aggregate(
+        1,
+        input,
+        context,
+        parse(input.as_bytes())?,
+        pipeline.into(),
+    )?)?;
+    Ok(top_three_elf_calories.into())
+}
@@ -0,0 +1,176 @@
+use anyhow::{anyhow, ensure, Error, Result};
+use mongodb::bson::{doc, Bson, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new).and_then(|line| {
+                Ok(match line.split_once(' ') {
+                    Some((op, arg)) => doc! { "op": op, "arg": arg.parse::<i32>()? },
+                    None if line == "noop" => doc! { "op": line },
+                    None => return Err(anyhow!("unknown instruction {}", line)),
+                })
+            })
+        })
+        .collect()
+}
+
+/// What each instruction adds to the register at the end of each cycle it
+/// takes.
+fn deltas() -> Expr {
+    cond(
+        eq(field("op"), "addx"),
+        array([Expr::from(0), field("arg")]),
+        array([0]),
+    )
+}
+
+/// One document per cycle with its 1-based `cycle` number and the register
+/// `x` during it.
+fn cycles(capabilities: &Capabilities) -> Pipeline {
+    if capabilities.window_fields {
+        return Pipeline::new()
+            .project([("line", field("line")), ("delta", deltas())])
+            .unwind_with_index("delta", "step")
+            .set_window_fields(
+                SetWindowFields::new()
+                    .sort_by(&[("line", 1), ("step", 1)])
+                    .output(
+                        "before",
+                        Window::sum(field("delta")).documents(Bound::Unbounded, Bound::Offset(-1)),
+                    )
+                    .output(
+                        "cycle",
+                        Window::count().documents(Bound::Unbounded, Bound::Current),
+                    ),
+            )
+            .project([
+                ("cycle", field("cycle")),
+                ("x", add([field("before"), 1.into()])),
+            ]);
+    }
+
+    // Without window functions, flatten the deltas in line order and fold
+    // them into the register's value during each cycle.
+    Pipeline::new()
+        .sort(&[("line", 1)])
+        .group(Group::by(Bson::Null).push("deltas", deltas()))
+        .project([(
+            "deltas",
+            reduce(
+                field("deltas"),
+                array::<Expr>([]),
+                concat_arrays([var("value"), var("this")]),
+            ),
+        )])
+        .project([(
+            "x",
+            reduce(
+                field("deltas"),
+                object([("x", 1.into()), ("xs", array::<Expr>([]))]),
+                object([
+                    ("x", add([var("value.x"), var("this")])),
+                    (
+                        "xs",
+                        concat_arrays([var("value.xs"), array([var("value.x")])]),
+                    ),
+                ]),
+            ),
+        )])
+        .project([("x", field("x.xs"))])
+        .unwind_with_index("x", "i")
+        .project([("cycle", add([field("i"), 1.into()])), ("x", field("x"))])
+}
+
+pub fn part1_pipeline(capabilities: &Capabilities) -> Pipeline {
+    cycles(capabilities)
+        .match_expr(and([
+            eq(modulo(field("cycle"), 40), 20),
+            lte(field("cycle"), 220),
+        ]))
+        .group(Group::by(Bson::Null).sum("signalStrength", multiply([field("cycle"), field("x")])))
+}
+
+pub fn part2_pipeline(capabilities: &Capabilities) -> Pipeline {
+    let position = || subtract(field("cycle"), 1);
+    let column = || modulo(position(), 40);
+    let offset = subtract(field("x"), column());
+    cycles(capabilities)
+        .match_expr(lte(field("cycle"), 240))
+        .project([
+            ("cycle", field("cycle")),
+            ("row", divide(subtract(position(), column()), 40)),
+            (
+                "pixel",
+                cond(and([gte(offset.clone(), -1), lte(offset, 1)]), "#", "."),
+            ),
+        ])
+        .sort(&[("cycle", 1)])
+        .group(Group::by(field("row")).push("pixels", field("pixel")))
+        .sort(&[("_id", 1)])
+        .group(Group::by(Bson::Null).push(
+            "rows",
+            reduce(field("pixels"), "", concat([var("value"), var("this")])),
+        ))
+        .project([(
+            "image",
+            reduce(
+                field("rows"),
+                "",
+                cond(
+                    eq(var("value"), ""),
+                    var("this"),
+                    concat([var("value"), "\n".into(), var("this")]),
+                ),
+            ),
+        )])
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignalStrength {
+    signal_strength: Integer,
+}
+
+#[derive(Default, Deserialize)]
+struct Image {
+    image: String,
+}
+
+fn aggregate<E: Engine>(
+    input: &str,
+    context: &Context,
+    pipeline: Pipeline,
+) -> Result<Vec<Document>> {
+    E::aggregate(
+        10,
+        input,
+        context,
+        parse(input.as_bytes())?,
+        pipeline.into(),
+    )
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part1_pipeline(&E::capabilities(context)?);
+    let SignalStrength { signal_strength } =
+        mongo::single(&aggregate::<E>(input, context, pipeline)?)?;
+    Ok(signal_strength.into())
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part2_pipeline(&E::capabilities(context)?);
+    let documents = aggregate::<E>(input, context, pipeline)?;
+    // Unlike the other answers, there's none without a program to draw.
+    ensure!(!documents.is_empty(), "couldn't draw an image");
+    let Image { image } = mongo::single(&documents)?;
+    Ok(image.into())
+}
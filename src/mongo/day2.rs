@@ -0,0 +1,303 @@
+use anyhow::{anyhow, bail, Error, Result};
+use mongodb::bson::{self, doc, Bson, Document};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    day2::{self, Analysis, Outcome, Rules},
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new)
+                .and_then(|c| {
+                    c.split_once(' ')
+                        .map(|(a, b)| (a.to_owned(), b.to_owned()))
+                        .ok_or_else(|| anyhow!("Couldn't parse line"))
+                })
+                .map(|(opp, self_)| {
+                    doc! { "self": self_, "opp": opp }
+                })
+        })
+        .collect()
+}
+
+/// The index in `symbols` of the symbol in `field_`.
+fn index<'a>(symbols: impl IntoIterator<Item = &'a String>, field_: &str) -> Expr {
+    index_of_array(
+        array(symbols.into_iter().map(String::as_str)),
+        field(field_),
+    )
+}
+
+fn opp_index(rules: &Rules) -> Expr {
+    index(rules.shapes.iter().map(|s| &s.opponent), "opp")
+}
+
+/// The score for playing the shape indexed by `shape` with the outcome
+/// indexed by `outcome`.
+fn score(rules: &Rules, shape: Expr, outcome: Expr) -> Expr {
+    add([
+        array_elem_at(array(rules.shapes.iter().map(|s| s.score)), shape),
+        array_elem_at(
+            array(Outcome::ALL.map(|o| rules.outcome_rule(o).score)),
+            outcome,
+        ),
+    ])
+}
+
+/// The values in `list` that aren't null.
+fn non_null(list: Expr) -> Expr {
+    reduce(
+        list,
+        array::<Expr>([]),
+        cond(
+            eq(var("this"), Bson::Null),
+            var("value"),
+            concat_arrays([var("value"), array([var("this")])]),
+        ),
+    )
+}
+
+/// Groups the rounds into one document with the sums of `totals`, and the
+/// `unknown_opponents` and `unknown_strategies` symbols that aren't in the
+/// rules, in input order, with `strategy` indexing the strategy symbols.
+fn check_symbols(rules: &Rules, strategy: Expr, totals: Vec<(&str, Expr)>) -> Pipeline {
+    let unknown = |index: Expr, symbol| cond(eq(index, -1), field(symbol), Bson::Null);
+    let fields = totals.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let mut group = Group::by(0)
+        .push("unknown_opponents", unknown(opp_index(rules), "opp"))
+        .push("unknown_strategies", unknown(strategy, "self"));
+    for (name, total) in totals {
+        group = group.sum(name, total);
+    }
+    Pipeline::new().sort(&[("line", 1)]).group(group).project(
+        fields.into_iter().map(|name| (name, field(name))).chain([
+            ("unknown_opponents", non_null(field("unknown_opponents"))),
+            ("unknown_strategies", non_null(field("unknown_strategies"))),
+        ]),
+    )
+}
+
+#[derive(Default, Deserialize)]
+struct Symbols {
+    unknown_opponents: Vec<String>,
+    unknown_strategies: Vec<String>,
+}
+
+impl Symbols {
+    /// Fails like the native solvers on the first unknown symbol, checking
+    /// the opponent's before the strategy's.
+    fn check(&self) -> Result<()> {
+        if let Some(symbol) = self.unknown_opponents.first() {
+            return Err(day2::unknown_opponent(symbol));
+        }
+        if let Some(symbol) = self.unknown_strategies.first() {
+            return Err(day2::unknown_strategy(symbol));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct TotalScore {
+    total_score: Integer,
+    #[serde(flatten)]
+    symbols: Symbols,
+}
+
+fn total_score(rules: &Rules, strategy: Expr, score: Expr) -> Pipeline {
+    check_symbols(rules, strategy, vec![("total_score", score)])
+}
+
+/// The table of outcome indexes by shape and then opponent.
+fn outcomes(rules: &Rules) -> Expr {
+    let shapes = 0..rules.shapes.len();
+    array(
+        shapes
+            .clone()
+            .map(|shape| array(shapes.clone().map(|opp| rules.outcome(shape, opp) as i32))),
+    )
+}
+
+/// The score for playing the shape indexed by `shape` against the opponent.
+fn shape_score(rules: &Rules, shape: Expr) -> Expr {
+    score(
+        rules,
+        shape.clone(),
+        array_elem_at(array_elem_at(outcomes(rules), shape), opp_index(rules)),
+    )
+}
+
+fn shape_index(rules: &Rules) -> Expr {
+    index(rules.shapes.iter().map(|s| &s.player), "self")
+}
+
+pub fn part1_pipeline(_: &Capabilities, rules: &Rules) -> Pipeline {
+    total_score(
+        rules,
+        shape_index(rules),
+        shape_score(rules, shape_index(rules)),
+    )
+}
+
+/// The shape played for each outcome against each opponent comes from a
+/// table of shape indexes by outcome and then opponent.
+pub fn part2_pipeline(_: &Capabilities, rules: &Rules) -> Pipeline {
+    let responses = array(Outcome::ALL.map(|outcome| {
+        array((0..rules.shapes.len()).map(|opp| rules.response(opp, outcome) as i32))
+    }));
+    let outcome = index(
+        Outcome::ALL.iter().map(|&o| &rules.outcome_rule(o).player),
+        "self",
+    );
+    total_score(
+        rules,
+        outcome.clone(),
+        score(
+            rules,
+            array_elem_at(array_elem_at(responses, outcome.clone()), opp_index(rules)),
+            outcome,
+        ),
+    )
+}
+
+fn run<E: Engine>(input: &str, context: &Context, pipeline: Pipeline) -> Result<Answer> {
+    let TotalScore {
+        total_score,
+        symbols,
+    } = mongo::single(&E::aggregate(
+        2,
+        input,
+        context,
+        parse(input.as_bytes())?,
+        pipeline.into(),
+    )?)?;
+    symbols.check()?;
+    Ok(total_score.into())
+}
+
+/// The symbols in the input that aren't in the rules, when the strategy
+/// symbols are read as shapes.
+pub fn symbols_pipeline(_: &Capabilities, rules: &Rules) -> Pipeline {
+    check_symbols(rules, shape_index(rules), vec![])
+}
+
+/// The rounds the opponent played each shape in, as `count` by shape index in
+/// `_id`.
+pub fn opponent_pipeline(_: &Capabilities, rules: &Rules) -> Pipeline {
+    Pipeline::new()
+        .project([("opp", opp_index(rules))])
+        .group(Group::by(field("opp")).sum("count", 1))
+        .sort(&[("_id", 1)])
+}
+
+/// The `total` score of the rounds with each strategy symbol when it's read
+/// as each shape, by the indexes of the shape the symbol stands for, `symbol`,
+/// and the shape it's read as, `shape`, in `_id`.
+pub fn totals_pipeline(_: &Capabilities, rules: &Rules) -> Pipeline {
+    Pipeline::new()
+        .project([
+            ("opp", field("opp")),
+            ("symbol", shape_index(rules)),
+            ("shape", range(0, rules.shapes.len() as i32)),
+        ])
+        .unwind("shape")
+        .project([
+            ("symbol", field("symbol")),
+            ("shape", field("shape")),
+            ("score", shape_score(rules, field("shape"))),
+        ])
+        .group(
+            Group::by(object([
+                ("symbol", field("symbol")),
+                ("shape", field("shape")),
+            ]))
+            .sum("total", field("score")),
+        )
+}
+
+#[derive(Deserialize)]
+struct Count {
+    #[serde(rename = "_id")]
+    shape: Integer,
+    count: Integer,
+}
+
+#[derive(Deserialize)]
+struct Reading {
+    symbol: Integer,
+    shape: Integer,
+}
+
+#[derive(Deserialize)]
+struct Total {
+    #[serde(rename = "_id")]
+    reading: Reading,
+    total: Integer,
+}
+
+fn results<E: Engine, T: DeserializeOwned>(
+    input: &str,
+    context: &Context,
+    pipeline: Pipeline,
+) -> Result<Vec<T>> {
+    E::aggregate(2, input, context, parse(input.as_bytes())?, pipeline.into())?
+        .into_iter()
+        .map(|document| Ok(bson::from_document(document)?))
+        .collect()
+}
+
+/// [`day2::analyze`] as aggregations.
+pub fn analyze<E: Engine>(input: &str, context: &Context) -> Result<Analysis> {
+    let capabilities = E::capabilities(context)?;
+    let rules = &context.rules;
+    for symbols in results::<E, Symbols>(input, context, symbols_pipeline(&capabilities, rules))? {
+        symbols.check()?;
+    }
+    let mut opponent = vec![0; rules.shapes.len()];
+    for Count { shape, count } in
+        results::<E, Count>(input, context, opponent_pipeline(&capabilities, rules))?
+    {
+        *usize::try_from(shape.0)
+            .ok()
+            .and_then(|shape| opponent.get_mut(shape))
+            .ok_or_else(|| anyhow!("unknown opponent move in the input"))? = count.0;
+    }
+    let n = rules.shapes.len();
+    let mut totals = vec![vec![0; n]; n];
+    for Total { reading, total } in
+        results::<E, Total>(input, context, totals_pipeline(&capabilities, rules))?
+    {
+        let index = |i: Integer| usize::try_from(i.0).ok().filter(|&i| i < n);
+        let (Some(symbol), Some(shape)) = (index(reading.symbol), index(reading.shape)) else {
+            bail!(
+                "no reading of shape {} as {}",
+                reading.symbol.0,
+                reading.shape.0
+            );
+        };
+        totals[symbol][shape] = total.0;
+    }
+    Analysis::new(rules, opponent, &totals)
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(
+        input,
+        context,
+        part1_pipeline(&E::capabilities(context)?, &context.rules),
+    )
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(
+        input,
+        context,
+        part2_pipeline(&E::capabilities(context)?, &context.rules),
+    )
+}
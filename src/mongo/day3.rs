@@ -0,0 +1,219 @@
+use anyhow::{bail, Error, Result};
+use mongodb::bson::{doc, Bson, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    day3::{self, Config, Problem},
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+/// Maps an item to its 1-based position in an alphabet, or 0 if it's not in
+/// it.
+pub const PRIORITY: &str = "function(c, alphabet) {
+  return [...alphabet].indexOf(c) + 1
+}";
+
+/// What [`PRIORITY`] computes, for running the pipelines offline.
+pub fn priority(args: &[Bson]) -> Result<Bson> {
+    let (c, alphabet) = match args {
+        [Bson::String(c), Bson::String(alphabet)] => (c, alphabet),
+        _ => bail!("expected an item and an alphabet, got {:?}", args),
+    };
+    let priority = alphabet
+        .chars()
+        .position(|a| c.chars().eq([a]))
+        .map_or(0, |i| i + 1);
+    Ok(Bson::Double(priority as f64))
+}
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new).map(|items| {
+                doc! { "items": items }
+            })
+        })
+        .collect()
+}
+
+/// Scores documents with the `first` and `last` lines of a rucksack or group,
+/// a `sizeProblem` message or null, and the items `common` to its
+/// rucksacks. The result has the `prioritySum` of those with one common item
+/// in the alphabet, and the `problems` with the rest.
+fn score(pipeline: Pipeline, capabilities: &Capabilities, alphabet: &str) -> Pipeline {
+    let badge = || first(field("common"));
+    let priority = if capabilities.javascript {
+        function(PRIORITY, [badge(), alphabet.into()])
+    } else {
+        add([index_of_cp(alphabet, badge()), 1.into()])
+    };
+    let single = || eq(size(field("common")), 1);
+    let problem = cond(
+        eq(field("sizeProblem"), Bson::Null),
+        cond(
+            eq(size(field("common")), 0),
+            day3::NO_COMMON_ITEM,
+            cond(
+                gt(size(field("common")), 1),
+                day3::SEVERAL_COMMON_ITEMS,
+                cond(
+                    eq(field("priority"), 0),
+                    concat([
+                        "common item ".into(),
+                        badge(),
+                        " is not in the alphabet".into(),
+                    ]),
+                    Bson::Null,
+                ),
+            ),
+        ),
+        field("sizeProblem"),
+    );
+    pipeline
+        .project([
+            ("first", field("first")),
+            ("last", field("last")),
+            ("sizeProblem", field("sizeProblem")),
+            ("common", field("common")),
+            ("priority", cond(single(), priority, 0)),
+        ])
+        .project([
+            ("first", field("first")),
+            ("last", field("last")),
+            ("priority", field("priority")),
+            ("message", problem),
+        ])
+        .sort(&[("first", 1)])
+        .group(
+            Group::by(Bson::Null)
+                .sum(
+                    "prioritySum",
+                    cond(eq(field("message"), Bson::Null), field("priority"), 0),
+                )
+                .push(
+                    "problems",
+                    object([
+                        ("first", field("first")),
+                        ("last", field("last")),
+                        ("message", field("message")),
+                    ]),
+                ),
+        )
+        .project([
+            ("prioritySum", field("prioritySum")),
+            (
+                "problems",
+                reduce(
+                    field("problems"),
+                    array::<Expr>([]),
+                    cond(
+                        eq(var("this.message"), Bson::Null),
+                        var("value"),
+                        concat_arrays([var("value"), array([var("this")])]),
+                    ),
+                ),
+            ),
+        ])
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrioritySum {
+    priority_sum: Integer,
+    problems: Vec<Problem>,
+}
+
+pub fn part1_pipeline(capabilities: &Capabilities, config: &Config) -> Pipeline {
+    let n = || size(field("items"));
+    let half = || divide(subtract(n(), modulo(n(), 2)), 2);
+    let pipeline = Pipeline::new()
+        .project([
+            ("line", field("line")),
+            ("items", code_points(field("items"))),
+        ])
+        .project([
+            ("first", field("line")),
+            ("last", field("line")),
+            (
+                "sizeProblem",
+                cond(eq(modulo(n(), 2), 1), day3::ODD, Bson::Null),
+            ),
+            (
+                "common",
+                set_intersection([
+                    take(field("items"), half()),
+                    take(field("items"), subtract(0, half())),
+                ]),
+            ),
+        ]);
+    score(pipeline, capabilities, &config.alphabet)
+}
+
+/// Each group's common items are the intersection of its rucksacks' items,
+/// reduced over however many there are.
+pub fn part2_pipeline(capabilities: &Capabilities, config: &Config) -> Pipeline {
+    let group_size = i32::try_from(config.group_size).unwrap_or(i32::MAX);
+    let i = || subtract(field("line"), 1);
+    let pipeline = Pipeline::new()
+        .group(
+            Group::by(divide(subtract(i(), modulo(i(), group_size)), group_size))
+                .push("groupItems", code_points(field("items")))
+                .min("first", field("line"))
+                .max("last", field("line")),
+        )
+        .project([
+            ("first", field("first")),
+            ("last", field("last")),
+            (
+                "sizeProblem",
+                cond(
+                    lt(size(field("groupItems")), group_size),
+                    day3::short_group(config.group_size).as_str(),
+                    Bson::Null,
+                ),
+            ),
+            (
+                "common",
+                reduce(
+                    field("groupItems"),
+                    array_elem_at(field("groupItems"), 0),
+                    set_intersection([var("value"), var("this")]),
+                ),
+            ),
+        ]);
+    score(pipeline, capabilities, &config.alphabet)
+}
+
+fn run<E: Engine>(input: &str, context: &Context, pipeline: Pipeline) -> Result<Answer> {
+    let PrioritySum {
+        priority_sum,
+        problems,
+    } = mongo::single(&E::aggregate(
+        3,
+        input,
+        context,
+        parse(input.as_bytes())?,
+        pipeline.into(),
+    )?)?;
+    day3::check(&problems)?;
+    Ok(priority_sum.into())
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(
+        input,
+        context,
+        part1_pipeline(&E::capabilities(context)?, &context.rucksacks),
+    )
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(
+        input,
+        context,
+        part2_pipeline(&E::capabilities(context)?, &context.rucksacks),
+    )
+}
@@ -0,0 +1,86 @@
+use anyhow::{Error, Result};
+use mongodb::bson::{doc, Bson, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new).map(|items| {
+                doc! { "pairs": items }
+            })
+        })
+        .collect()
+}
+
+fn bounds() -> Pipeline {
+    let bound = |pair, end| to_int(split_at(split_at(field("pairs"), ",", pair), "-", end));
+    Pipeline::new().project([
+        ("s1", bound(0, 0)),
+        ("e1", bound(0, 1)),
+        ("s2", bound(1, 0)),
+        ("e2", bound(1, 1)),
+    ])
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Contained {
+    num_contained: Integer,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Overlapping {
+    num_overlapping: Integer,
+}
+
+/// Counts the pairs meeting `condition` into the field `name`.
+fn count(name: &str, condition: Expr) -> Pipeline {
+    bounds().group(Group::by(Bson::Null).sum(name, to_int(condition)))
+}
+
+pub fn part1_pipeline(_: &Capabilities) -> Pipeline {
+    let (s1, e1, s2, e2) = (field("s1"), field("e1"), field("s2"), field("e2"));
+    count(
+        "numContained",
+        or([
+            and([gte(s2.clone(), s1.clone()), lte(e2.clone(), e1.clone())]),
+            and([gte(s1, s2), lte(e1, e2)]),
+        ]),
+    )
+}
+
+pub fn part2_pipeline(_: &Capabilities) -> Pipeline {
+    count(
+        "numOverlapping",
+        and([lte(field("s1"), field("e2")), lte(field("s2"), field("e1"))]),
+    )
+}
+
+fn aggregate<E: Engine>(
+    input: &str,
+    context: &Context,
+    pipeline: Pipeline,
+) -> Result<Vec<Document>> {
+    E::aggregate(4, input, context, parse(input.as_bytes())?, pipeline.into())
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part1_pipeline(&E::capabilities(context)?);
+    let Contained { num_contained } = mongo::single(&aggregate::<E>(input, context, pipeline)?)?;
+    Ok(num_contained.into())
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part2_pipeline(&E::capabilities(context)?);
+    let Overlapping { num_overlapping } =
+        mongo::single(&aggregate::<E>(input, context, pipeline)?)?;
+    Ok(num_overlapping.into())
+}
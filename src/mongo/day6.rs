@@ -0,0 +1,84 @@
+use anyhow::{ensure, Error, Result};
+use mongodb::bson::{doc, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer},
+    Answer, Context,
+};
+
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            l.map_err(Error::new)
+                .map(|signal| doc! { "signal": signal })
+        })
+        .collect()
+}
+
+/// The number of characters read when the last `n` were all different.
+fn marker(capabilities: &Capabilities, n: i32) -> Pipeline {
+    let pipeline =
+        Pipeline::new().project([("line", field("line")), ("c", chars(field("signal")))]);
+    if capabilities.window_fields {
+        return pipeline
+            .unwind_with_index("c", "i")
+            .set_window_fields(
+                SetWindowFields::new()
+                    .partition_by(field("line"))
+                    .sort_by(&[("i", 1)])
+                    .output(
+                        "seen",
+                        Window::add_to_set(field("c"))
+                            .documents(Bound::Offset(1 - n), Bound::Current),
+                    ),
+            )
+            .match_expr(eq(size(field("seen")), n))
+            .group(Group::by(field("line")).min("marker", add([field("i"), 1.into()])));
+    }
+
+    // Without window functions, count the distinct characters in every slice
+    // of `n` and find the first with no repeats.
+    pipeline
+        .project([(
+            "distinct",
+            map(
+                range(0, subtract(size(field("c")), n - 1)),
+                size(set_union([slice(field("c"), var("this"), n)])),
+            ),
+        )])
+        .project([("start", index_of_array(field("distinct"), n))])
+        .match_expr(gte(field("start"), 0))
+        .project([("marker", add([field("start"), n.into()]))])
+}
+
+pub fn part1_pipeline(capabilities: &Capabilities) -> Pipeline {
+    marker(capabilities, 4)
+}
+
+pub fn part2_pipeline(capabilities: &Capabilities) -> Pipeline {
+    marker(capabilities, 14)
+}
+
+#[derive(Default, Deserialize)]
+struct Marker {
+    marker: Integer,
+}
+
+fn run<E: Engine>(input: &str, context: &Context, pipeline: Pipeline) -> Result<Answer> {
+    let documents = E::aggregate(6, input, context, parse(input.as_bytes())?, pipeline.into())?;
+    // Unlike the other answers, there's none without a marker.
+    ensure!(!documents.is_empty(), "couldn't find a marker");
+    let Marker { marker } = mongo::single(&documents)?;
+    Ok(marker.into())
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(input, context, part1_pipeline(&E::capabilities(context)?))
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    run::<E>(input, context, part2_pipeline(&E::capabilities(context)?))
+}
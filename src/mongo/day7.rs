@@ -0,0 +1,125 @@
+use anyhow::{anyhow, ensure, Result};
+use mongodb::bson::{doc, Bson, Document};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    mongo::{self, pipeline::*, Capabilities, Engine, Integer, INPUT},
+    Answer, Context,
+};
+
+const DISK: i64 = 70000000;
+const NEEDED: i64 = 30000000;
+
+/// One document per line of the transcript. Listed directories and files
+/// become `dir` and `file` documents with their `path` and their `parent`
+/// directory's path, and the first `cd /` becomes the root directory.
+/// Directory paths end in `/`.
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Document>> {
+    let mut cwd: Vec<String> = vec![];
+    let mut root = false;
+    let path = |cwd: &[String]| {
+        cwd.iter()
+            .fold("/".to_string(), |path, dir| format!("{}{}/", path, dir))
+    };
+    BufReader::new(reader)
+        .lines()
+        .map(|line| {
+            let line = line?;
+            Ok(if line == "$ cd /" {
+                cwd.clear();
+                if root {
+                    doc! { "type": "command" }
+                } else {
+                    root = true;
+                    doc! { "type": "dir", "path": "/", "parent": Bson::Null }
+                }
+            } else if line == "$ cd .." {
+                cwd.pop();
+                doc! { "type": "command" }
+            } else if let Some(dir) = line.strip_prefix("$ cd ") {
+                cwd.push(dir.to_string());
+                doc! { "type": "command" }
+            } else if line.starts_with('$') {
+                doc! { "type": "command" }
+            } else if let Some(dir) = line.strip_prefix("dir ") {
+                let parent = path(&cwd);
+                doc! { "type": "dir", "path": format!("{}{}/", parent, dir), "parent": parent }
+            } else {
+                let (size, name) = line
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("couldn't split"))?;
+                let parent = path(&cwd);
+                doc! {
+                    "type": "file",
+                    "path": format!("{}{}", parent, name),
+                    "parent": parent,
+                    "size": size.parse::<i64>()?,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Each directory's total `size`, summed over every file below it.
+fn dir_sizes() -> Pipeline {
+    Pipeline::new()
+        .match_expr(eq(field("type"), "dir"))
+        .graph_lookup(INPUT, field("path"), "path", "parent", "descendants")
+        .project([("size", sum(map(field("descendants"), var("this.size"))))])
+}
+
+pub fn part1_pipeline(_: &Capabilities) -> Pipeline {
+    dir_sizes()
+        .match_expr(lte(field("size"), 100000))
+        .group(Group::by(Bson::Null).sum("smallDirTotal", field("size")))
+}
+
+/// The root is the largest directory, so its size is the space used.
+pub fn part2_pipeline(_: &Capabilities) -> Pipeline {
+    dir_sizes()
+        .group(
+            Group::by(Bson::Null)
+                .push("size", field("size"))
+                .max("used", field("size")),
+        )
+        .unwind("size")
+        .match_expr(gt(field("size"), subtract(field("used"), DISK - NEEDED)))
+        .group(Group::by(Bson::Null).min("smallestDir", field("size")))
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SmallDirTotal {
+    small_dir_total: Integer,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SmallestDir {
+    smallest_dir: Integer,
+}
+
+fn aggregate<E: Engine>(
+    input: &str,
+    context: &Context,
+    pipeline: Pipeline,
+) -> Result<Vec<Document>> {
+    E::aggregate(7, input, context, parse(input.as_bytes())?, pipeline.into())
+}
+
+pub fn part1<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part1_pipeline(&E::capabilities(context)?);
+    let SmallDirTotal { small_dir_total } =
+        mongo::single(&aggregate::<E>(input, context, pipeline)?)?;
+    Ok(small_dir_total.into())
+}
+
+pub fn part2<E: Engine>(input: &str, context: &Context) -> Result<Answer> {
+    let pipeline = part2_pipeline(&E::capabilities(context)?);
+    let documents = aggregate::<E>(input, context, pipeline)?;
+    // Unlike the other answers, there's none without directories to delete.
+    ensure!(!documents.is_empty(), "couldn't find a directory to delete");
+    let SmallestDir { smallest_dir } = mongo::single(&documents)?;
+    Ok(smallest_dir.into())
+}
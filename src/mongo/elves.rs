@@ -0,0 +1,90 @@
+//! Day 1's per-elf calorie report, materialized in the `elves` collection so
+//! it can be queried without rerunning the pipeline.
+
+use anyhow::Result;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, Document},
+    options::ClientOptions,
+    Client,
+};
+use serde::Deserialize;
+use tokio::runtime::Runtime;
+
+use super::{config, day1, pipeline::*, Engine, Integer, Server};
+use crate::{input, Context};
+
+/// The collection in the configured database the report is merged into.
+pub const COLLECTION: &str = "elves";
+
+/// One elf's line of the report.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Elf {
+    pub elf: Integer,
+    pub items: Integer,
+    pub total_calories: Integer,
+    pub rank: Integer,
+    pub percentile: f64,
+}
+
+/// Which of an input's elves to return, best first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Query {
+    /// Only elves carrying more calories than this.
+    pub more_than: Option<i64>,
+    /// Only elves ranked this or better.
+    pub top: Option<i64>,
+}
+
+/// Runs over the `elves` collection, selecting the report of the input with
+/// `input_hash`.
+pub fn query_pipeline(input_hash: &str, query: &Query) -> Pipeline {
+    let mut conditions = vec![eq(field("inputHash"), input_hash)];
+    if let Some(more_than) = query.more_than {
+        conditions.push(gt(field("totalCalories"), more_than));
+    }
+    if let Some(top) = query.top {
+        conditions.push(lte(field("rank"), top));
+    }
+    Pipeline::new()
+        .match_expr(and(conditions))
+        .sort(&[("rank", 1), ("elf", 1)])
+}
+
+/// Computes the report for `input` on the server and merges it into the
+/// `elves` collection, replacing any earlier report of the same input.
+pub fn report(input: &str, context: &Context) -> Result<()> {
+    let pipeline = day1::report_pipeline(&Server::capabilities(context)?, &input::hash(input))
+        .merge(COLLECTION);
+    Server::aggregate(
+        1,
+        input,
+        context,
+        day1::parse(input.as_bytes())?,
+        pipeline.into(),
+    )?;
+    Ok(())
+}
+
+async fn query_(pipeline: Vec<Document>) -> Result<Vec<Document>> {
+    let config = config();
+    let client = Client::with_options(ClientOptions::parse(&config.uri).await?)?;
+    Ok(client
+        .database(&config.database)
+        .collection::<Document>(COLLECTION)
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect()
+        .await?)
+}
+
+/// The elves in the stored report of `input` that match `query`.
+pub fn query(input: &str, query: &Query) -> Result<Vec<Elf>> {
+    let pipeline = query_pipeline(&input::hash(input), query);
+    Runtime::new()?
+        .block_on(query_(pipeline.into()))?
+        .into_iter()
+        .map(|document| Ok(bson::from_document(document)?))
+        .collect()
+}
@@ -0,0 +1,1329 @@
+//! An in-process interpreter for the subset of the aggregation framework the
+//! pipeline solvers use, so pipelines can be run without a server.
+//!
+//! Supported stages are `$setWindowFields` (`$sum`, `$count`, `$push` and
+//! `$addToSet` over `documents` windows, and `$rank`), `$match` (only `$expr`), `$group`
+//! (`$sum`, `$min`, `$max`, `$topN` and `$push`), `$graphLookup` (from the
+//! input collection), `$project`, `$sort`, `$limit` and `$unwind`. Anything
+//! else is reported as unsupported rather than approximated.
+
+use anyhow::{anyhow, bail, ensure, Result};
+use mongodb::bson::{Bson, Document};
+use std::{cmp::Ordering, collections::HashMap, time::Instant};
+
+use super::{explain, Capabilities, INPUT};
+
+/// A Rust stand-in for the JavaScript body of a `$function` expression.
+pub type Function = fn(&[Bson]) -> Result<Bson>;
+
+/// Runs pipelines over in-memory documents.
+#[derive(Default)]
+pub struct Evaluator {
+    functions: HashMap<String, Function>,
+    capabilities: Capabilities,
+}
+
+type Vars<'a> = [(&'a str, Bson)];
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Evaluator::default()
+    }
+
+    /// Runs `function` wherever a `$function` expression's body is `body`.
+    pub fn with_function(mut self, body: &str, function: Function) -> Self {
+        self.functions.insert(body.trim().to_string(), function);
+        self
+    }
+
+    /// Rejects features a server with only `capabilities` wouldn't have.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Runs `pipeline` over `documents` as if they had just been inserted into
+    /// a collection, giving each an increasing `_id` if it doesn't have one.
+    pub fn aggregate(
+        &self,
+        documents: Vec<Document>,
+        pipeline: &[Document],
+    ) -> Result<Vec<Document>> {
+        Ok(self.explain(documents, pipeline)?.0)
+    }
+
+    /// Like [`aggregate`](Self::aggregate), also saying how each stage ran.
+    pub fn explain(
+        &self,
+        documents: Vec<Document>,
+        pipeline: &[Document],
+    ) -> Result<(Vec<Document>, Vec<explain::Stage>)> {
+        let mut documents = documents
+            .into_iter()
+            .enumerate()
+            .map(|(i, document)| {
+                if document.contains_key("_id") {
+                    document
+                } else {
+                    let mut with_id = Document::new();
+                    with_id.insert("_id", i as i64);
+                    with_id.extend(document);
+                    with_id
+                }
+            })
+            .collect::<Vec<Document>>();
+        let input = documents.clone();
+
+        let mut stages = vec![];
+        for stage in pipeline {
+            let (name, spec) = operator(stage)?;
+            let docs_in = documents.len() as u64;
+            let start = Instant::now();
+            documents = match name {
+                "$limit" => {
+                    let n = integer(spec)
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow!("$limit takes a positive integer"))?;
+                    documents.truncate(n as usize);
+                    documents
+                }
+                "$unwind" => self.unwind(documents, spec)?,
+                _ => {
+                    let spec = spec
+                        .as_document()
+                        .ok_or_else(|| anyhow!("{} takes a document, got {}", name, spec))?;
+                    match name {
+                        "$setWindowFields" => {
+                            ensure!(
+                                self.capabilities.window_fields,
+                                "$setWindowFields isn't available before MongoDB 5.0"
+                            );
+                            self.set_window_fields(documents, spec)?
+                        }
+                        "$graphLookup" => self.graph_lookup(documents, spec, &input)?,
+                        "$match" => self.match_(documents, spec)?,
+                        "$group" => self.group(documents, spec)?,
+                        "$project" => self.project(documents, spec)?,
+                        "$sort" => sort(documents, spec)?,
+                        _ => bail!("unsupported stage {}", name),
+                    }
+                }
+            };
+            stages.push(explain::Stage {
+                name: name.to_string(),
+                docs_in: Some(docs_in),
+                docs_out: Some(documents.len() as u64),
+                elapsed: Some(start.elapsed()),
+                spilled: false,
+            });
+        }
+
+        Ok((documents, stages))
+    }
+
+    fn set_window_fields(
+        &self,
+        documents: Vec<Document>,
+        spec: &Document,
+    ) -> Result<Vec<Document>> {
+        for key in spec.keys() {
+            ensure!(
+                ["partitionBy", "sortBy", "output"].contains(&key.as_str()),
+                "unsupported $setWindowFields option {}",
+                key
+            );
+        }
+
+        let mut keyed = documents
+            .into_iter()
+            .map(|document| {
+                let partition = match spec.get("partitionBy") {
+                    Some(expr) => self.eval(expr, &document, &[])?,
+                    None => Bson::Null,
+                };
+                Ok((partition, document))
+            })
+            .collect::<Result<Vec<(Bson, Document)>>>()?;
+        let sort_by = match spec.get("sortBy") {
+            Some(sort_by) => Some(
+                sort_by
+                    .as_document()
+                    .ok_or_else(|| anyhow!("$setWindowFields sortBy must be a document"))?,
+            ),
+            None => None,
+        };
+        let mut error = None;
+        keyed.sort_by(|(p1, d1), (p2, d2)| {
+            compare(p1, p2).then_with(|| match sort_by {
+                Some(sort_by) => compare_by(sort_by, d1, d2).unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                }),
+                None => Ordering::Equal,
+            })
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        let output = spec
+            .get_document("output")
+            .map_err(|_| anyhow!("$setWindowFields requires an output document"))?;
+        let mut outputs = vec![];
+        for (field, spec) in output {
+            let spec = spec
+                .as_document()
+                .ok_or_else(|| anyhow!("$setWindowFields output {} must be a document", field))?;
+            let mut accumulator = None;
+            let mut window = None;
+            for (key, value) in spec {
+                match key.as_str() {
+                    "window" => window = Some(window_bounds(value)?),
+                    "$sum" | "$count" | "$push" | "$addToSet" | "$rank" => {
+                        accumulator = Some((key.as_str(), value))
+                    }
+                    _ => bail!("unsupported $setWindowFields operator {}", key),
+                }
+            }
+            let (accumulator, expr) =
+                accumulator.ok_or_else(|| anyhow!("no window operator for {}", field))?;
+            if accumulator == "$rank" {
+                ensure!(window.is_none(), "$rank doesn't take a window");
+                ensure!(sort_by.is_some(), "$rank requires sortBy");
+            }
+            outputs.push((field, accumulator, expr, window));
+        }
+
+        let mut results = Vec::with_capacity(keyed.len());
+        let mut start = 0;
+        while start < keyed.len() {
+            let end = start
+                + keyed[start..]
+                    .iter()
+                    .take_while(|(p, _)| compare(p, &keyed[start].0) == Ordering::Equal)
+                    .count();
+            let partition = &keyed[start..end];
+
+            let mut values = vec![];
+            for (_, accumulator, expr, _) in &outputs {
+                values.push(if *accumulator == "$count" {
+                    vec![Bson::Int32(1); partition.len()]
+                } else if *accumulator == "$rank" {
+                    rank(partition, sort_by.unwrap_or(&Document::new()))?
+                } else {
+                    partition
+                        .iter()
+                        .map(|(_, d)| self.eval(expr, d, &[]))
+                        .collect::<Result<Vec<Bson>>>()?
+                });
+            }
+
+            for (i, (_, document)) in partition.iter().enumerate() {
+                let mut document = document.clone();
+                for ((field, accumulator, _, window), values) in outputs.iter().zip(&values) {
+                    let (lo, hi) = match window {
+                        Some((lo, hi)) => (
+                            lo.map_or(0, |lo| (i as i64 + lo).max(0)),
+                            hi.map_or(partition.len() as i64 - 1, |hi| {
+                                (i as i64 + hi).min(partition.len() as i64 - 1)
+                            }),
+                        ),
+                        None => (0, partition.len() as i64 - 1),
+                    };
+                    let window = if lo <= hi {
+                        &values[lo as usize..=hi as usize]
+                    } else {
+                        &[]
+                    };
+                    let present = window.iter().filter(|v| **v != Bson::Undefined);
+                    let value = match *accumulator {
+                        "$rank" => values[i].clone(),
+                        "$push" => Bson::Array(present.cloned().collect()),
+                        "$addToSet" => Bson::Array(distinct(present)),
+                        _ => sum(window.iter())?,
+                    };
+                    document.insert(field.as_str(), value);
+                }
+                results.push(document);
+            }
+
+            start = end;
+        }
+
+        Ok(results)
+    }
+
+    /// A `$graphLookup` from the input collection, the only one there is.
+    fn graph_lookup(
+        &self,
+        documents: Vec<Document>,
+        spec: &Document,
+        input: &[Document],
+    ) -> Result<Vec<Document>> {
+        for key in spec.keys() {
+            ensure!(
+                [
+                    "from",
+                    "startWith",
+                    "connectFromField",
+                    "connectToField",
+                    "as"
+                ]
+                .contains(&key.as_str()),
+                "unsupported $graphLookup option {}",
+                key
+            );
+        }
+        let option = |key: &str| {
+            spec.get(key)
+                .ok_or_else(|| anyhow!("$graphLookup requires {}", key))
+        };
+        let from = string(option("from")?, "$graphLookup from")?;
+        ensure!(
+            from == INPUT,
+            "unsupported $graphLookup from {}; only the input collection is available",
+            from
+        );
+        let start_with = option("startWith")?;
+        let connect_from = string(option("connectFromField")?, "$graphLookup connectFromField")?;
+        let connect_to = string(option("connectToField")?, "$graphLookup connectToField")?;
+        let as_ = string(option("as")?, "$graphLookup as")?;
+
+        // Values of `connect_to` a document matches, which includes every
+        // element if it's an array.
+        let values = |value: Bson| match value {
+            Bson::Array(items) => items,
+            Bson::Undefined => vec![],
+            value => vec![value],
+        };
+        let mut results = vec![];
+        for mut document in documents {
+            let mut found = vec![false; input.len()];
+            let mut frontier = values(self.eval(start_with, &document, &[])?);
+            while !frontier.is_empty() {
+                let mut next = vec![];
+                for (i, candidate) in input.iter().enumerate() {
+                    if found[i] {
+                        continue;
+                    }
+                    let connects = values(get_path(candidate, connect_to))
+                        .iter()
+                        .any(|v| frontier.iter().any(|f| compare(v, f) == Ordering::Equal));
+                    if connects {
+                        found[i] = true;
+                        next.extend(values(get_path(candidate, connect_from)));
+                    }
+                }
+                frontier = next;
+            }
+            let matches = input
+                .iter()
+                .zip(found)
+                .filter(|(_, found)| *found)
+                .map(|(candidate, _)| Bson::Document(candidate.clone()))
+                .collect();
+            document.insert(as_, Bson::Array(matches));
+            results.push(document);
+        }
+        Ok(results)
+    }
+
+    fn match_(&self, documents: Vec<Document>, spec: &Document) -> Result<Vec<Document>> {
+        let mut expr = None;
+        for (key, value) in spec {
+            match key.as_str() {
+                "$expr" => expr = Some(value),
+                _ => bail!(
+                    "unsupported $match query on {}; only $expr is supported",
+                    key
+                ),
+            }
+        }
+        let expr = expr.ok_or_else(|| anyhow!("$match requires $expr"))?;
+        let mut results = vec![];
+        for document in documents {
+            if truthy(&self.eval(expr, &document, &[])?) {
+                results.push(document);
+            }
+        }
+        Ok(results)
+    }
+
+    fn group(&self, documents: Vec<Document>, spec: &Document) -> Result<Vec<Document>> {
+        let id = spec
+            .get("_id")
+            .ok_or_else(|| anyhow!("$group requires an _id"))?;
+
+        let mut groups: Vec<(Bson, Vec<Document>)> = vec![];
+        for document in documents {
+            let key = self.eval(id, &document, &[])?;
+            let key = if key == Bson::Undefined {
+                Bson::Null
+            } else {
+                key
+            };
+            match groups
+                .iter_mut()
+                .find(|(k, _)| compare(k, &key) == Ordering::Equal)
+            {
+                Some((_, group)) => group.push(document),
+                None => groups.push((key, vec![document])),
+            }
+        }
+
+        let mut results = vec![];
+        for (key, group) in groups {
+            let mut result = Document::new();
+            result.insert("_id", key);
+            for (field, accumulator) in spec {
+                if field == "_id" {
+                    continue;
+                }
+                let accumulator = accumulator
+                    .as_document()
+                    .ok_or_else(|| anyhow!("$group field {} must be an accumulator", field))?;
+                let (name, expr) = operator(accumulator)?;
+                let value = match name {
+                    "$sum" => sum(group
+                        .iter()
+                        .map(|d| self.eval(expr, d, &[]))
+                        .collect::<Result<Vec<Bson>>>()?
+                        .iter())?,
+                    "$min" | "$max" => {
+                        let values = group
+                            .iter()
+                            .map(|d| self.eval(expr, d, &[]))
+                            .collect::<Result<Vec<Bson>>>()?;
+                        let values = values
+                            .into_iter()
+                            .filter(|v| !matches!(v, Bson::Null | Bson::Undefined));
+                        if name == "$min" {
+                            values.min_by(compare)
+                        } else {
+                            values.max_by(compare)
+                        }
+                        .unwrap_or(Bson::Null)
+                    }
+                    "$push" => Bson::Array(
+                        group
+                            .iter()
+                            .map(|d| self.eval(expr, d, &[]))
+                            .filter(|v| !matches!(v, Ok(Bson::Undefined)))
+                            .collect::<Result<Vec<Bson>>>()?,
+                    ),
+                    "$topN" => {
+                        ensure!(
+                            self.capabilities.top_n,
+                            "$topN isn't available before MongoDB 5.2"
+                        );
+                        self.top_n(&group, expr)?
+                    }
+                    _ => bail!("unsupported $group accumulator {}", name),
+                };
+                result.insert(field.as_str(), value);
+            }
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    fn top_n(&self, group: &[Document], spec: &Bson) -> Result<Bson> {
+        let spec = spec
+            .as_document()
+            .ok_or_else(|| anyhow!("$topN takes a document"))?;
+        let output = spec
+            .get("output")
+            .ok_or_else(|| anyhow!("$topN requires output"))?;
+        let sort_by = spec
+            .get_document("sortBy")
+            .map_err(|_| anyhow!("$topN requires a sortBy document"))?;
+        let n = spec
+            .get("n")
+            .and_then(integer)
+            .filter(|n| *n > 0)
+            .ok_or_else(|| anyhow!("$topN requires a positive integer n"))?;
+
+        let mut sorted = group.iter().collect::<Vec<&Document>>();
+        let mut error = None;
+        sorted.sort_by(|d1, d2| {
+            compare_by(sort_by, d1, d2).unwrap_or_else(|e| {
+                error.get_or_insert(e);
+                Ordering::Equal
+            })
+        });
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        Ok(Bson::Array(
+            sorted
+                .into_iter()
+                .take(n as usize)
+                .map(|d| self.eval(output, d, &[]).map(present_or_null))
+                .collect::<Result<Vec<Bson>>>()?,
+        ))
+    }
+
+    fn unwind(&self, documents: Vec<Document>, spec: &Bson) -> Result<Vec<Document>> {
+        let (path, index) = match spec {
+            Bson::String(path) => (path.as_str(), None),
+            Bson::Document(spec) => {
+                for key in spec.keys() {
+                    ensure!(
+                        ["path", "includeArrayIndex"].contains(&key.as_str()),
+                        "unsupported $unwind option {}",
+                        key
+                    );
+                }
+                let path = spec
+                    .get_str("path")
+                    .map_err(|_| anyhow!("$unwind requires a path"))?;
+                let index = match spec.get("includeArrayIndex") {
+                    Some(index) => Some(string(index, "$unwind includeArrayIndex")?),
+                    None => None,
+                };
+                (path, index)
+            }
+            _ => bail!("unsupported $unwind {}", spec),
+        };
+        let field = path
+            .strip_prefix('$')
+            .filter(|f| !f.contains('.'))
+            .ok_or_else(|| anyhow!("unsupported $unwind path {}", path))?;
+        let mut results = vec![];
+        for document in documents {
+            match document.get(field) {
+                Some(Bson::Array(items)) => {
+                    for (i, item) in items.iter().enumerate() {
+                        let mut unwound = document.clone();
+                        unwound.insert(field, item.clone());
+                        if let Some(index) = index {
+                            unwound.insert(index, i as i64);
+                        }
+                        results.push(unwound);
+                    }
+                }
+                None | Some(Bson::Null) => {}
+                Some(_) => {
+                    let mut document = document;
+                    if let Some(index) = index {
+                        document.insert(index, Bson::Null);
+                    }
+                    results.push(document)
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn project(&self, documents: Vec<Document>, spec: &Document) -> Result<Vec<Document>> {
+        let mut include_id = true;
+        let mut fields = vec![];
+        for (field, value) in spec {
+            ensure!(
+                !field.contains('.') && !field.starts_with('$'),
+                "unsupported $project field {}",
+                field
+            );
+            match value {
+                Bson::Boolean(_) | Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) => {
+                    if truthy(value) {
+                        fields.push((field, None));
+                    } else if field == "_id" {
+                        include_id = false;
+                    } else {
+                        bail!("unsupported $project exclusion of {}", field);
+                    }
+                }
+                _ => fields.push((field, Some(value))),
+            }
+        }
+
+        documents
+            .into_iter()
+            .map(|document| {
+                let mut result = Document::new();
+                if include_id && !spec.contains_key("_id") {
+                    if let Some(id) = document.get("_id") {
+                        result.insert("_id", id.clone());
+                    }
+                }
+                for (field, expr) in &fields {
+                    let value = match expr {
+                        Some(expr) => self.eval(expr, &document, &[])?,
+                        None => get_path(&document, field),
+                    };
+                    if value != Bson::Undefined {
+                        result.insert(field.as_str(), value);
+                    }
+                }
+                Ok(result)
+            })
+            .collect()
+    }
+
+    /// Evaluates an aggregation expression against `document`. Missing
+    /// values evaluate to `Bson::Undefined`.
+    fn eval(&self, expr: &Bson, document: &Document, vars: &Vars) -> Result<Bson> {
+        match expr {
+            Bson::String(s) if s.starts_with("$$") => {
+                let mut path = s[2..].split('.');
+                let name = path.next().unwrap_or_default();
+                let value = match name {
+                    "ROOT" | "CURRENT" => Bson::Document(document.clone()),
+                    _ => vars
+                        .iter()
+                        .rev()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, v)| v.clone())
+                        .ok_or_else(|| anyhow!("undefined variable {}", name))?,
+                };
+                Ok(path.fold(value, |value, field| get(&value, field)))
+            }
+            Bson::String(s) if s.starts_with('$') => Ok(get_path(document, &s[1..])),
+            Bson::Array(items) => Ok(Bson::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item, document, vars).map(present_or_null))
+                    .collect::<Result<Vec<Bson>>>()?,
+            )),
+            Bson::Document(d) if d.keys().any(|k| k.starts_with('$')) => {
+                let (name, args) = operator(d)?;
+                self.eval_operator(name, args, document, vars)
+            }
+            Bson::Document(d) => {
+                let mut result = Document::new();
+                for (field, expr) in d {
+                    let value = self.eval(expr, document, vars)?;
+                    if value != Bson::Undefined {
+                        result.insert(field.as_str(), value);
+                    }
+                }
+                Ok(Bson::Document(result))
+            }
+            _ => Ok(expr.clone()),
+        }
+    }
+
+    fn eval_args(&self, args: &Bson, document: &Document, vars: &Vars) -> Result<Vec<Bson>> {
+        match args {
+            Bson::Array(args) => args
+                .iter()
+                .map(|arg| self.eval(arg, document, vars))
+                .collect(),
+            arg => Ok(vec![self.eval(arg, document, vars)?]),
+        }
+    }
+
+    fn eval_operator(
+        &self,
+        name: &str,
+        args: &Bson,
+        document: &Document,
+        vars: &Vars,
+    ) -> Result<Bson> {
+        match name {
+            "$literal" => return Ok(args.clone()),
+            "$map" => return self.map(args, document, vars),
+            "$reduce" => return self.reduce(args, document, vars),
+            "$function" => return self.function(args, document, vars),
+            "$cond" => {
+                if let Bson::Document(spec) = args {
+                    let field =
+                        |f: &str| spec.get(f).ok_or_else(|| anyhow!("$cond requires {}", f));
+                    return if truthy(&self.eval(field("if")?, document, vars)?) {
+                        self.eval(field("then")?, document, vars)
+                    } else {
+                        self.eval(field("else")?, document, vars)
+                    };
+                }
+            }
+            _ => {}
+        }
+
+        let args = self.eval_args(args, document, vars)?;
+        let arity = |n: usize| {
+            ensure!(
+                args.len() == n,
+                "{} takes {} arguments, got {}",
+                name,
+                n,
+                args.len()
+            );
+            Ok(())
+        };
+        let nullish = |args: &[Bson]| {
+            args.iter()
+                .any(|a| matches!(a, Bson::Null | Bson::Undefined))
+        };
+
+        Ok(match name {
+            "$cond" => {
+                arity(3)?;
+                if truthy(&args[0]) {
+                    args[1].clone()
+                } else {
+                    args[2].clone()
+                }
+            }
+            "$and" => Bson::Boolean(args.iter().all(truthy)),
+            "$or" => Bson::Boolean(args.iter().any(truthy)),
+            "$eq" | "$ne" | "$lt" | "$lte" | "$gt" | "$gte" => {
+                arity(2)?;
+                let ordering = compare(&args[0], &args[1]);
+                Bson::Boolean(match name {
+                    "$eq" => ordering == Ordering::Equal,
+                    "$ne" => ordering != Ordering::Equal,
+                    "$lt" => ordering == Ordering::Less,
+                    "$lte" => ordering != Ordering::Greater,
+                    "$gt" => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                })
+            }
+            "$add" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let mut total = Number::Int(0);
+                for arg in &args {
+                    total = total.add(number(arg, name)?);
+                }
+                total.into()
+            }
+            "$multiply" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let mut product = Number::Int(1);
+                for arg in &args {
+                    product = product.mul(number(arg, name)?);
+                }
+                product.into()
+            }
+            "$subtract" => {
+                arity(2)?;
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                number(&args[0], name)?
+                    .add(number(&args[1], name)?.neg())
+                    .into()
+            }
+            "$divide" => {
+                arity(2)?;
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let divisor = number(&args[1], name)?.as_f64();
+                ensure!(divisor != 0.0, "can't $divide by zero");
+                Bson::Double(number(&args[0], name)?.as_f64() / divisor)
+            }
+            "$mod" => {
+                arity(2)?;
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let (dividend, divisor) = (number(&args[0], name)?, number(&args[1], name)?);
+                ensure!(divisor.as_f64() != 0.0, "can't $mod by zero");
+                dividend.rem(divisor).into()
+            }
+            "$sum" => match args.as_slice() {
+                [Bson::Array(items)] => sum(items.iter())?,
+                _ => sum(args.iter())?,
+            },
+            "$first" => {
+                arity(1)?;
+                match &args[0] {
+                    Bson::Array(items) => items.first().cloned().unwrap_or(Bson::Undefined),
+                    Bson::Null | Bson::Undefined => Bson::Null,
+                    arg => bail!("$first takes an array, got {}", arg),
+                }
+            }
+            "$arrayElemAt" => {
+                arity(2)?;
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let items = array(&args[0], name)?;
+                let index = integer(&args[1])
+                    .ok_or_else(|| anyhow!("$arrayElemAt index must be an integer"))?;
+                let index = if index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    index
+                };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .cloned()
+                    .unwrap_or(Bson::Undefined)
+            }
+            "$indexOfArray" => {
+                arity(2)?;
+                if matches!(args[0], Bson::Null | Bson::Undefined) {
+                    return Ok(Bson::Null);
+                }
+                let index = array(&args[0], name)?
+                    .iter()
+                    .position(|item| compare(item, &args[1]) == Ordering::Equal);
+                Number::Int(index.map_or(-1, |i| i as i64)).into()
+            }
+            "$range" => {
+                ensure!(
+                    args.len() == 2 || args.len() == 3,
+                    "$range takes 2 or 3 arguments"
+                );
+                let bound = |arg: &Bson| {
+                    integer(arg)
+                        .ok_or_else(|| anyhow!("$range bounds must be integers, got {}", arg))
+                };
+                let (start, end) = (bound(&args[0])?, bound(&args[1])?);
+                let step = args.get(2).map_or(Ok(1), bound)?;
+                ensure!(step != 0, "$range step can't be zero");
+                let mut items = vec![];
+                let mut i = start;
+                while (step > 0 && i < end) || (step < 0 && i > end) {
+                    items.push(Number::Int(i).into());
+                    i += step;
+                }
+                Bson::Array(items)
+            }
+            "$setIntersection" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let sets = args
+                    .iter()
+                    .map(|arg| array(arg, name))
+                    .collect::<Result<Vec<&Vec<Bson>>>>()?;
+                let mut items: Vec<Bson> = vec![];
+                if let Some((first, rest)) = sets.split_first() {
+                    for item in first.iter() {
+                        let in_all = rest.iter().all(|set| {
+                            set.iter()
+                                .any(|other| compare(item, other) == Ordering::Equal)
+                        });
+                        let seen = items
+                            .iter()
+                            .any(|other| compare(item, other) == Ordering::Equal);
+                        if in_all && !seen {
+                            items.push(item.clone());
+                        }
+                    }
+                }
+                Bson::Array(items)
+            }
+            "$setUnion" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let mut items = vec![];
+                for arg in &args {
+                    items.extend(array(arg, name)?.iter());
+                }
+                Bson::Array(distinct(items.into_iter()))
+            }
+            "$size" => {
+                arity(1)?;
+                Bson::Int32(i32::try_from(array(&args[0], name)?.len())?)
+            }
+            "$slice" => {
+                ensure!(
+                    args.len() == 2 || args.len() == 3,
+                    "$slice takes 2 or 3 arguments, got {}",
+                    args.len()
+                );
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let items = array(&args[0], name)?;
+                let len = items.len() as i64;
+                let (start, n) = match &args[1..] {
+                    [n] => {
+                        let n = number(n, name)?.truncate();
+                        if n < 0 {
+                            ((len + n).max(0), -n)
+                        } else {
+                            (0, n)
+                        }
+                    }
+                    [position, n] => {
+                        let position = number(position, name)?.truncate();
+                        let n = number(n, name)?.truncate();
+                        ensure!(n > 0, "$slice takes a positive count, got {}", n);
+                        let start = if position < 0 {
+                            (len + position).max(0)
+                        } else {
+                            position.min(len)
+                        };
+                        (start, n)
+                    }
+                    _ => unreachable!(),
+                };
+                let end = (start + n).min(len);
+                Bson::Array(items[start as usize..end as usize].to_vec())
+            }
+            "$concatArrays" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let mut items = vec![];
+                for arg in &args {
+                    items.extend(array(arg, name)?.iter().cloned());
+                }
+                Bson::Array(items)
+            }
+            "$concat" => {
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let mut result = String::new();
+                for arg in &args {
+                    result.push_str(string(arg, name)?);
+                }
+                Bson::String(result)
+            }
+            "$indexOfCP" => {
+                arity(2)?;
+                if matches!(args[0], Bson::Null | Bson::Undefined) {
+                    return Ok(Bson::Null);
+                }
+                let s = string(&args[0], name)?;
+                let index = s
+                    .find(string(&args[1], name)?)
+                    .map_or(-1, |i| s[..i].chars().count() as i64);
+                Number::Int(index).into()
+            }
+            "$strLenCP" => {
+                arity(1)?;
+                Number::Int(string(&args[0], name)?.chars().count() as i64).into()
+            }
+            "$substrCP" => {
+                arity(3)?;
+                if matches!(args[0], Bson::Null | Bson::Undefined) {
+                    return Ok(Bson::String(String::new()));
+                }
+                let s = string(&args[0], name)?;
+                let start = number(&args[1], name)?.truncate();
+                let len = number(&args[2], name)?.truncate();
+                ensure!(
+                    start >= 0 && len >= 0,
+                    "$substrCP start and length can't be negative"
+                );
+                Bson::String(s.chars().skip(start as usize).take(len as usize).collect())
+            }
+            "$strLenBytes" => {
+                arity(1)?;
+                Number::Int(string(&args[0], name)?.len() as i64).into()
+            }
+            "$substrBytes" => {
+                arity(3)?;
+                if matches!(args[0], Bson::Null | Bson::Undefined) {
+                    return Ok(Bson::String(String::new()));
+                }
+                let s = string(&args[0], name)?;
+                let start = number(&args[1], name)?.truncate();
+                let len = number(&args[2], name)?.truncate();
+                ensure!(start >= 0, "$substrBytes start can't be negative");
+                let start = (start as usize).min(s.len());
+                let end = if len < 0 {
+                    s.len()
+                } else {
+                    (start + len as usize).min(s.len())
+                };
+                Bson::String(
+                    s.get(start..end)
+                        .ok_or_else(|| anyhow!("$substrBytes splits a UTF-8 character in {}", s))?
+                        .to_string(),
+                )
+            }
+            "$split" => {
+                arity(2)?;
+                if nullish(&args) {
+                    return Ok(Bson::Null);
+                }
+                let delimiter = string(&args[1], name)?;
+                ensure!(!delimiter.is_empty(), "$split delimiter can't be empty");
+                Bson::Array(
+                    string(&args[0], name)?
+                        .split(delimiter)
+                        .map(|s| Bson::String(s.to_string()))
+                        .collect(),
+                )
+            }
+            "$toInt" => {
+                arity(1)?;
+                match &args[0] {
+                    Bson::Null | Bson::Undefined => Bson::Null,
+                    Bson::Boolean(b) => Bson::Int32(i32::from(*b)),
+                    Bson::String(s) => Bson::Int32(
+                        s.parse()
+                            .map_err(|_| anyhow!("$toInt can't convert {:?}", s))?,
+                    ),
+                    arg => Bson::Int32(i32::try_from(number(arg, name)?.truncate())?),
+                }
+            }
+            _ => bail!("unsupported expression operator {}", name),
+        })
+    }
+
+    fn map(&self, spec: &Bson, document: &Document, vars: &Vars) -> Result<Bson> {
+        let spec = spec
+            .as_document()
+            .ok_or_else(|| anyhow!("$map takes a document"))?;
+        let input = self.eval(
+            spec.get("input")
+                .ok_or_else(|| anyhow!("$map requires input"))?,
+            document,
+            vars,
+        )?;
+        let name = match spec.get("as") {
+            Some(Bson::String(name)) => name.as_str(),
+            Some(name) => bail!("$map as must be a string, got {}", name),
+            None => "this",
+        };
+        let expr = spec.get("in").ok_or_else(|| anyhow!("$map requires in"))?;
+        let items = match input {
+            Bson::Null | Bson::Undefined => return Ok(Bson::Null),
+            Bson::Array(items) => items,
+            input => bail!("$map input must be an array, got {}", input),
+        };
+        let mut vars = vars.to_vec();
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            vars.push((name, item));
+            results.push(present_or_null(self.eval(expr, document, &vars)?));
+            vars.pop();
+        }
+        Ok(Bson::Array(results))
+    }
+
+    fn reduce(&self, spec: &Bson, document: &Document, vars: &Vars) -> Result<Bson> {
+        let spec = spec
+            .as_document()
+            .ok_or_else(|| anyhow!("$reduce takes a document"))?;
+        let arg = |f: &str| spec.get(f).ok_or_else(|| anyhow!("$reduce requires {}", f));
+        let items = match self.eval(arg("input")?, document, vars)? {
+            Bson::Null | Bson::Undefined => return Ok(Bson::Null),
+            Bson::Array(items) => items,
+            input => bail!("$reduce input must be an array, got {}", input),
+        };
+        let expr = arg("in")?;
+        let mut value = self.eval(arg("initialValue")?, document, vars)?;
+        let mut vars = vars.to_vec();
+        for item in items {
+            vars.push(("value", value));
+            vars.push(("this", item));
+            value = self.eval(expr, document, &vars)?;
+            vars.truncate(vars.len() - 2);
+        }
+        Ok(value)
+    }
+
+    fn function(&self, spec: &Bson, document: &Document, vars: &Vars) -> Result<Bson> {
+        let spec = spec
+            .as_document()
+            .ok_or_else(|| anyhow!("$function takes a document"))?;
+        ensure!(
+            self.capabilities.javascript,
+            "$function needs server-side JavaScript, which is disabled"
+        );
+        let lang = spec.get_str("lang").unwrap_or_default();
+        ensure!(lang == "js", "unsupported $function lang {:?}", lang);
+        let body = spec
+            .get_str("body")
+            .map_err(|_| anyhow!("$function requires a string body"))?;
+        let function = self
+            .functions
+            .get(body.trim())
+            .ok_or_else(|| anyhow!("unsupported $function; no Rust implementation of {}", body))?;
+        let args = match spec.get("args") {
+            Some(args) => self.eval_args(args, document, vars)?,
+            None => vec![],
+        };
+        function(&args)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Number {
+    Int(i64),
+    Double(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(n) => n as f64,
+            Number::Double(n) => n,
+        }
+    }
+
+    fn truncate(self) -> i64 {
+        match self {
+            Number::Int(n) => n,
+            Number::Double(n) => n as i64,
+        }
+    }
+
+    // Integers that overflow become doubles, as they do on a server.
+    fn add(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a
+                .checked_add(b)
+                .map_or(Number::Double(a as f64 + b as f64), Number::Int),
+            (a, b) => Number::Double(a.as_f64() + b.as_f64()),
+        }
+    }
+
+    fn rem(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a
+                .checked_rem(b)
+                .map_or(Number::Double(a as f64 % b as f64), Number::Int),
+            (a, b) => Number::Double(a.as_f64() % b.as_f64()),
+        }
+    }
+
+    fn mul(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a
+                .checked_mul(b)
+                .map_or(Number::Double(a as f64 * b as f64), Number::Int),
+            (a, b) => Number::Double(a.as_f64() * b.as_f64()),
+        }
+    }
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Int(n) => n
+                .checked_neg()
+                .map_or(Number::Double(-(n as f64)), Number::Int),
+            Number::Double(n) => Number::Double(-n),
+        }
+    }
+}
+
+impl From<Number> for Bson {
+    fn from(n: Number) -> Self {
+        match n {
+            Number::Int(n) => i32::try_from(n).map_or(Bson::Int64(n), Bson::Int32),
+            Number::Double(n) => Bson::Double(n),
+        }
+    }
+}
+
+fn as_number(value: &Bson) -> Option<Number> {
+    match value {
+        Bson::Int32(n) => Some(Number::Int((*n).into())),
+        Bson::Int64(n) => Some(Number::Int(*n)),
+        Bson::Double(n) => Some(Number::Double(*n)),
+        _ => None,
+    }
+}
+
+fn number(value: &Bson, operator: &str) -> Result<Number> {
+    as_number(value)
+        .ok_or_else(|| anyhow!("{} only supports numeric types, got {}", operator, value))
+}
+
+fn integer(value: &Bson) -> Option<i64> {
+    match as_number(value)? {
+        Number::Int(n) => Some(n),
+        Number::Double(n) if n.fract() == 0.0 => Some(n as i64),
+        Number::Double(_) => None,
+    }
+}
+
+fn string<'a>(value: &'a Bson, operator: &str) -> Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| anyhow!("{} takes a string, got {}", operator, value))
+}
+
+fn array<'a>(value: &'a Bson, operator: &str) -> Result<&'a Vec<Bson>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("{} takes an array, got {}", operator, value))
+}
+
+/// Sums the numbers among `values`, ignoring everything else like `$sum` does.
+fn sum<'a>(values: impl Iterator<Item = &'a Bson>) -> Result<Bson> {
+    Ok(values
+        .filter_map(as_number)
+        .fold(Number::Int(0), Number::add)
+        .into())
+}
+
+/// `values` without repeats, in the order they first appear.
+fn distinct<'a>(values: impl Iterator<Item = &'a Bson>) -> Vec<Bson> {
+    let mut items: Vec<Bson> = vec![];
+    for value in values {
+        if !items
+            .iter()
+            .any(|item| compare(item, value) == Ordering::Equal)
+        {
+            items.push(value.clone());
+        }
+    }
+    items
+}
+
+/// The single `$operator` key of a stage or expression and its argument.
+fn operator(document: &Document) -> Result<(&str, &Bson)> {
+    let mut entries = document.iter();
+    match (entries.next(), entries.next()) {
+        (Some((name, args)), None) if name.starts_with('$') => Ok((name.as_str(), args)),
+        _ => bail!("expected a single $operator, got {}", document),
+    }
+}
+
+/// Follows a dotted field path like `a.b` from `document`.
+fn get_path(document: &Document, path: &str) -> Bson {
+    let mut fields = path.split('.');
+    let first = fields.next().unwrap_or_default();
+    let value = document.get(first).cloned().unwrap_or(Bson::Undefined);
+    fields.fold(value, |value, field| get(&value, field))
+}
+
+fn get(value: &Bson, field: &str) -> Bson {
+    match value {
+        Bson::Document(d) => d.get(field).cloned().unwrap_or(Bson::Undefined),
+        _ => Bson::Undefined,
+    }
+}
+
+/// Missing values become null inside arrays, as they do on the server.
+fn present_or_null(value: Bson) -> Bson {
+    if value == Bson::Undefined {
+        Bson::Null
+    } else {
+        value
+    }
+}
+
+fn truthy(value: &Bson) -> bool {
+    match value {
+        Bson::Null | Bson::Undefined | Bson::Boolean(false) => false,
+        value => as_number(value).is_none_or(|n| n.as_f64() != 0.0),
+    }
+}
+
+/// Each document's `$rank` in a partition already ordered by `sort_by`.
+fn rank(partition: &[(Bson, Document)], sort_by: &Document) -> Result<Vec<Bson>> {
+    let mut ranks: Vec<Bson> = Vec::with_capacity(partition.len());
+    for (i, (_, document)) in partition.iter().enumerate() {
+        let rank = match i.checked_sub(1) {
+            Some(previous)
+                if compare_by(sort_by, &partition[previous].1, document)? == Ordering::Equal =>
+            {
+                ranks[previous].clone()
+            }
+            _ => Bson::Int32(i as i32 + 1),
+        };
+        ranks.push(rank);
+    }
+    Ok(ranks)
+}
+
+fn window_bounds(window: &Bson) -> Result<(Option<i64>, Option<i64>)> {
+    let window = window
+        .as_document()
+        .ok_or_else(|| anyhow!("window must be a document"))?;
+    let mut bounds = None;
+    for (key, value) in window {
+        match (key.as_str(), value) {
+            ("documents", Bson::Array(b)) if b.len() == 2 => bounds = Some((&b[0], &b[1])),
+            _ => bail!(
+                "unsupported window {}; only documents windows are supported",
+                key
+            ),
+        }
+    }
+    let (lo, hi) = bounds.ok_or_else(|| anyhow!("window requires documents bounds"))?;
+    let bound = |b: &Bson| match b {
+        Bson::String(s) if s == "unbounded" => Ok(None),
+        Bson::String(s) if s == "current" => Ok(Some(0)),
+        b => integer(b)
+            .map(Some)
+            .ok_or_else(|| anyhow!("unsupported window bound {}", b)),
+    };
+    Ok((bound(lo)?, bound(hi)?))
+}
+
+fn sort(mut documents: Vec<Document>, sort_by: &Document) -> Result<Vec<Document>> {
+    let mut error = None;
+    documents.sort_by(|d1, d2| {
+        compare_by(sort_by, d1, d2).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            Ordering::Equal
+        })
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(documents),
+    }
+}
+
+/// Orders documents by a `sortBy` specification of field paths and directions.
+fn compare_by(sort_by: &Document, d1: &Document, d2: &Document) -> Result<Ordering> {
+    for (field, direction) in sort_by {
+        let (v1, v2) = (get_path(d1, field), get_path(d2, field));
+        let ordering = match integer(direction) {
+            Some(1) => compare(&v1, &v2),
+            Some(-1) => compare(&v2, &v1),
+            _ => bail!("sort direction for {} must be 1 or -1", field),
+        };
+        if ordering != Ordering::Equal {
+            return Ok(ordering);
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+fn type_order(value: &Bson) -> u8 {
+    match value {
+        Bson::MinKey => 0,
+        Bson::Undefined => 1,
+        Bson::Null => 2,
+        Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => 3,
+        Bson::String(_) | Bson::Symbol(_) => 4,
+        Bson::Document(_) => 5,
+        Bson::Array(_) => 6,
+        Bson::Binary(_) => 7,
+        Bson::ObjectId(_) => 8,
+        Bson::Boolean(_) => 9,
+        Bson::DateTime(_) => 10,
+        Bson::Timestamp(_) => 11,
+        Bson::RegularExpression(_) => 12,
+        Bson::MaxKey => 14,
+        _ => 13,
+    }
+}
+
+/// Orders values the way the server does: first by type, then by value.
+fn compare(a: &Bson, b: &Bson) -> Ordering {
+    match (a, b) {
+        (Bson::String(a), Bson::String(b)) => a.cmp(b),
+        (Bson::Boolean(a), Bson::Boolean(b)) => a.cmp(b),
+        (Bson::ObjectId(a), Bson::ObjectId(b)) => a.bytes().cmp(&b.bytes()),
+        (Bson::Array(a), Bson::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Bson::Document(a), Bson::Document(b)) => a
+            .iter()
+            .zip(b)
+            .map(|((ka, va), (kb, vb))| compare(va, vb).then_with(|| ka.cmp(kb)))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        _ => match (as_number(a), as_number(b)) {
+            (Some(Number::Int(a)), Some(Number::Int(b))) => a.cmp(&b),
+            (Some(a), Some(b)) => a.as_f64().total_cmp(&b.as_f64()),
+            _ => type_order(a).cmp(&type_order(b)),
+        },
+    }
+}
@@ -0,0 +1,138 @@
+//! Per-stage summaries of how an aggregation ran, from the server's `explain`
+//! output or the offline evaluator.
+
+use mongodb::bson::{Bson, Document};
+use std::{fmt::Write, time::Duration};
+
+/// How one stage of a pipeline ran.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    pub name: String,
+    pub docs_in: Option<u64>,
+    pub docs_out: Option<u64>,
+    /// Time spent in this stage alone.
+    pub elapsed: Option<Duration>,
+    /// Whether the stage wrote temporary data to disk.
+    pub spilled: bool,
+}
+
+fn count(document: &Document, field: &str) -> Option<u64> {
+    match document.get(field)? {
+        Bson::Int32(n) => u64::try_from(*n).ok(),
+        Bson::Int64(n) => u64::try_from(*n).ok(),
+        Bson::Double(n) if *n >= 0.0 => Some(*n as u64),
+        _ => None,
+    }
+}
+
+fn millis(document: &Document, field: &str) -> Option<Duration> {
+    count(document, field).map(Duration::from_millis)
+}
+
+/// Whether `pipeline` writes with `$merge` or `$out`, which servers won't
+/// explain with `executionStats` verbosity.
+pub fn writes(pipeline: &[Document]) -> bool {
+    pipeline
+        .iter()
+        .any(|stage| stage.contains_key("$merge") || stage.contains_key("$out"))
+}
+
+/// The stages in the output of an `explain` command with `executionStats`
+/// verbosity. Servers report each stage's time as a running total, so it's
+/// split back up here. A pipeline run entirely by the query engine has no
+/// stages of its own and is reported as a single `query` stage, and sharded
+/// pipelines list each shard's stages under the shard's name.
+pub fn stages(explain: &Document) -> Vec<Stage> {
+    if let Ok(stages) = explain.get_array("stages") {
+        return pipeline_stages(stages);
+    }
+    if let Ok(shards) = explain.get_document("shards") {
+        return shards
+            .iter()
+            .filter_map(|(shard, explain)| Some((shard, explain.as_document()?)))
+            .flat_map(|(shard, explain)| {
+                stages(explain).into_iter().map(move |stage| Stage {
+                    name: format!("{}: {}", shard, stage.name),
+                    ..stage
+                })
+            })
+            .collect();
+    }
+    match explain.get_document("executionStats") {
+        Ok(stats) => vec![Stage {
+            name: "query".to_string(),
+            docs_in: count(stats, "totalDocsExamined"),
+            docs_out: count(stats, "nReturned"),
+            elapsed: millis(stats, "executionTimeMillis"),
+            spilled: false,
+        }],
+        Err(_) => vec![],
+    }
+}
+
+fn pipeline_stages(stages: &[Bson]) -> Vec<Stage> {
+    let mut previous_out = None;
+    let mut previous_total = Duration::ZERO;
+    stages
+        .iter()
+        .filter_map(Bson::as_document)
+        .filter_map(|stage| {
+            let (name, spec) = stage.iter().next()?;
+            let cursor_stats = spec
+                .as_document()
+                .filter(|_| name == "$cursor")
+                .and_then(|cursor| cursor.get_document("executionStats").ok());
+            let docs_in = match cursor_stats {
+                Some(stats) => count(stats, "totalDocsExamined"),
+                None => previous_out,
+            };
+            let docs_out = count(stage, "nReturned")
+                .or_else(|| cursor_stats.and_then(|s| count(s, "nReturned")));
+            let total = millis(stage, "executionTimeMillisEstimate")
+                .or_else(|| cursor_stats.and_then(|s| millis(s, "executionTimeMillis")));
+            let elapsed = total.map(|total| total.saturating_sub(previous_total));
+            previous_out = docs_out;
+            previous_total = total.unwrap_or(previous_total);
+            Some(Stage {
+                name: name.clone(),
+                docs_in,
+                docs_out,
+                elapsed,
+                spilled: stage.get_bool("usedDisk") == Ok(true)
+                    || count(stage, "spills").unwrap_or(0) > 0,
+            })
+        })
+        .collect()
+}
+
+/// An aligned table of `stages`.
+pub fn summary(stages: &[Stage]) -> String {
+    let width = stages
+        .iter()
+        .map(|stage| stage.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("stage".len());
+    let optional = |n: Option<String>| n.unwrap_or_else(|| "-".to_string());
+    let mut summary = format!(
+        "{:<width$}  {:>10}  {:>10}  {:>12}  storage\n",
+        "stage",
+        "docs in",
+        "docs out",
+        "time",
+        width = width
+    );
+    for stage in stages {
+        let _ = writeln!(
+            summary,
+            "{:<width$}  {:>10}  {:>10}  {:>12}  {}",
+            stage.name,
+            optional(stage.docs_in.map(|n| n.to_string())),
+            optional(stage.docs_out.map(|n| n.to_string())),
+            optional(stage.elapsed.map(|d| format!("{:.3?}", d))),
+            if stage.spilled { "disk" } else { "memory" },
+            width = width
+        );
+    }
+    summary
+}
@@ -0,0 +1,173 @@
+//! mongosh scripts that load a day's input and run its pipelines, for trying
+//! them out in the shell or Compass.
+
+use anyhow::{anyhow, Result};
+use mongodb::bson::{Bson, Document};
+use std::fmt::Write;
+
+use super::{
+    annotate, config, day1, day10, day2, day3, day4, day6, day7, pipeline::Pipeline, resolve_input,
+    Capabilities,
+};
+use crate::Context;
+
+type Parse = fn(&str) -> Result<Vec<Document>>;
+type Build = fn(&Capabilities, &Context) -> Pipeline;
+
+/// Each day's parser and part 1 and part 2 pipelines.
+const DAYS: [(u8, Parse, Build, Build); 7] = [
+    (
+        1,
+        |input| day1::parse(input.as_bytes()),
+        |capabilities, _| day1::part1_pipeline(capabilities),
+        |capabilities, _| day1::part2_pipeline(capabilities),
+    ),
+    (
+        2,
+        |input| day2::parse(input.as_bytes()),
+        |capabilities, context| day2::part1_pipeline(capabilities, &context.rules),
+        |capabilities, context| day2::part2_pipeline(capabilities, &context.rules),
+    ),
+    (
+        3,
+        |input| day3::parse(input.as_bytes()),
+        |capabilities, context| day3::part1_pipeline(capabilities, &context.rucksacks),
+        |capabilities, context| day3::part2_pipeline(capabilities, &context.rucksacks),
+    ),
+    (
+        4,
+        |input| day4::parse(input.as_bytes()),
+        |capabilities, _| day4::part1_pipeline(capabilities),
+        |capabilities, _| day4::part2_pipeline(capabilities),
+    ),
+    (
+        6,
+        |input| day6::parse(input.as_bytes()),
+        |capabilities, _| day6::part1_pipeline(capabilities),
+        |capabilities, _| day6::part2_pipeline(capabilities),
+    ),
+    (
+        7,
+        |input| day7::parse(input.as_bytes()),
+        |capabilities, _| day7::part1_pipeline(capabilities),
+        |capabilities, _| day7::part2_pipeline(capabilities),
+    ),
+    (
+        10,
+        |input| day10::parse(input.as_bytes()),
+        |capabilities, _| day10::part1_pipeline(capabilities),
+        |capabilities, _| day10::part2_pipeline(capabilities),
+    ),
+];
+
+/// The days that have pipelines to export.
+pub fn days() -> impl Iterator<Item = u8> {
+    DAYS.iter().map(|(day, ..)| *day)
+}
+
+/// A day's input documents and pipelines, as the solvers would run them.
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub day: u8,
+    pub documents: Vec<Document>,
+    pub pipelines: [Vec<Document>; 2],
+}
+
+fn json(value: Bson) -> String {
+    value.into_relaxed_extjson().to_string()
+}
+
+fn pretty_json(value: Bson) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&value.into_relaxed_extjson())?)
+}
+
+impl Export {
+    /// Parses `input` for `day` and builds the pipeline variants a server with
+    /// `capabilities` can run, as the solvers would with `context`.
+    pub fn new(
+        day: u8,
+        input: &str,
+        context: &Context,
+        capabilities: &Capabilities,
+    ) -> Result<Self> {
+        let (_, parse, part1, part2) = DAYS
+            .iter()
+            .find(|(d, ..)| *d == day)
+            .ok_or_else(|| anyhow!("day {} has no pipelines", day))?;
+        Ok(Export {
+            day,
+            documents: annotate(input, &context.source, parse(input)?)?,
+            pipelines: [
+                part1(capabilities, context).into(),
+                part2(capabilities, context).into(),
+            ],
+        })
+    }
+
+    /// A mongosh script that reloads `collection` with the documents and
+    /// prints the results of both parts. Everything is written as relaxed
+    /// Extended JSON and read back with `EJSON.deserialize`.
+    pub fn script(&self, collection: &str) -> Result<String> {
+        let mut script = String::new();
+        writeln!(
+            script,
+            "// Advent of Code 2022 day {}, exported by `aoc export`.",
+            self.day
+        )?;
+        writeln!(
+            script,
+            "// Reloads the {} collection and prints both parts' results:",
+            collection
+        )?;
+        // The URI isn't written out, since it may carry credentials.
+        writeln!(
+            script,
+            "//   mongosh <uri>/{} day{}.js",
+            config().database,
+            self.day
+        )?;
+        writeln!(script)?;
+        writeln!(
+            script,
+            "const collection = db.getCollection({});",
+            json(collection.into())
+        )?;
+        let documents: Vec<String> = self
+            .documents
+            .iter()
+            .map(|document| format!("  {}", json(document.clone().into())))
+            .collect();
+        writeln!(
+            script,
+            "const documents = EJSON.deserialize([\n{}\n]);",
+            documents.join(",\n")
+        )?;
+        for (part, pipeline) in self.pipelines.iter().enumerate() {
+            let pipeline = pretty_json(Bson::Array(
+                resolve_input(pipeline.clone(), collection)
+                    .into_iter()
+                    .map(Bson::Document)
+                    .collect(),
+            ))?;
+            writeln!(
+                script,
+                "const part{} = EJSON.deserialize({});",
+                part + 1,
+                pipeline
+            )?;
+        }
+        writeln!(script)?;
+        writeln!(script, "collection.drop();")?;
+        writeln!(script, "collection.insertMany(documents);")?;
+        writeln!(
+            script,
+            "for (const [part, pipeline] of [[1, part1], [2, part2]]) {{"
+        )?;
+        writeln!(
+            script,
+            "  printjson({{ part, results: collection.aggregate(pipeline).toArray() }});"
+        )?;
+        writeln!(script, "}}")?;
+        Ok(script)
+    }
+}
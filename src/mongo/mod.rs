@@ -0,0 +1,470 @@
+use anyhow::{anyhow, bail, Result};
+use futures::{future::try_join_all, stream::TryStreamExt};
+use mongodb::{
+    bson::{self, doc, oid::ObjectId, Document},
+    error::{CommandError, ErrorKind},
+    options::{AggregateOptions, ClientOptions, InsertManyOptions},
+    Client, Collection, Database,
+};
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{any, fmt, sync::OnceLock, time::Duration};
+use tokio::runtime::Runtime;
+
+use crate::{input, Answer, Context, Implementation};
+pub use capabilities::Capabilities;
+
+pub mod capabilities;
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day6;
+pub mod day7;
+pub mod elves;
+pub mod eval;
+pub mod explain;
+pub mod export;
+pub mod pipeline;
+pub mod results;
+
+/// The days with aggregation pipeline solvers, run on a server.
+pub const DAYS: [Implementation; 7] = [
+    (1, day1::part1::<Server>, day1::part2::<Server>),
+    (2, day2::part1::<Server>, day2::part2::<Server>),
+    (3, day3::part1::<Server>, day3::part2::<Server>),
+    (4, day4::part1::<Server>, day4::part2::<Server>),
+    (6, day6::part1::<Server>, day6::part2::<Server>),
+    (7, day7::part1::<Server>, day7::part2::<Server>),
+    (10, day10::part1::<Server>, day10::part2::<Server>),
+];
+
+/// The same solvers run by the in-process evaluator.
+pub const OFFLINE_DAYS: [Implementation; 7] = [
+    (1, day1::part1::<Offline>, day1::part2::<Offline>),
+    (2, day2::part1::<Offline>, day2::part2::<Offline>),
+    (3, day3::part1::<Offline>, day3::part2::<Offline>),
+    (4, day4::part1::<Offline>, day4::part2::<Offline>),
+    (6, day6::part1::<Offline>, day6::part2::<Offline>),
+    (7, day7::part1::<Offline>, day7::part2::<Offline>),
+    (10, day10::part1::<Offline>, day10::part2::<Offline>),
+];
+
+/// Stands for the collection a run's input is loaded into wherever a stage
+/// names a collection, as `$graphLookup` does. Collection names can't contain
+/// `$`, so it can't clash with a real one.
+pub const INPUT: &str = "$input";
+
+/// Replaces [`INPUT`] with `collection` in the stages that name one.
+pub fn resolve_input(pipeline: Vec<Document>, collection: &str) -> Vec<Document> {
+    pipeline
+        .into_iter()
+        .map(|mut stage| {
+            if let Ok(spec) = stage.get_document_mut("$graphLookup") {
+                if spec.get_str("from") == Ok(INPUT) {
+                    spec.insert("from", collection);
+                }
+            }
+            stage
+        })
+        .collect()
+}
+
+/// Something that can run a day's pipeline over its input documents, which
+/// must be one per line of `input`.
+pub trait Engine {
+    /// What the pipelines may use, so they can pick compatible variants.
+    fn capabilities(context: &Context) -> Result<Capabilities>;
+
+    fn aggregate(
+        day: u8,
+        input: &str,
+        context: &Context,
+        documents: Vec<Document>,
+        pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>>;
+}
+
+/// A MongoDB server, as set by [`configure`]. Each run loads its input into a
+/// collection of its own, or a shared one named after the input's hash if
+/// [`Config::reuse`] or [`Config::keep_data`] is set.
+pub struct Server;
+
+/// The in-process [`eval::Evaluator`].
+pub struct Offline;
+
+static SERVER_CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+
+async fn capabilities_() -> Result<Capabilities> {
+    let client = Client::with_options(ClientOptions::parse(&config().uri).await?)?;
+    Capabilities::detect(&client.database("admin")).await
+}
+
+impl Engine for Server {
+    fn capabilities(_context: &Context) -> Result<Capabilities> {
+        if let Some(capabilities) = SERVER_CAPABILITIES.get() {
+            return Ok(*capabilities);
+        }
+        let capabilities = Runtime::new()?.block_on(capabilities_())?;
+        Ok(*SERVER_CAPABILITIES.get_or_init(|| capabilities))
+    }
+
+    fn aggregate(
+        day: u8,
+        input: &str,
+        context: &Context,
+        documents: Vec<Document>,
+        pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>> {
+        let documents = annotate(input, &context.source, documents)?;
+        Runtime::new()?.block_on(aggregate_(day, &input::hash(input), documents, pipeline))
+    }
+}
+
+impl Engine for Offline {
+    /// The context's emulated capabilities.
+    fn capabilities(context: &Context) -> Result<Capabilities> {
+        Ok(context.emulated)
+    }
+
+    fn aggregate(
+        day: u8,
+        input: &str,
+        context: &Context,
+        documents: Vec<Document>,
+        pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>> {
+        let evaluator = evaluator().with_capabilities(Self::capabilities(context)?);
+        let documents = annotate(input, &context.source, documents)?;
+        if !config().explain {
+            return evaluator.aggregate(documents, &pipeline);
+        }
+        let (results, stages) = evaluator.explain(documents, &pipeline)?;
+        report(day, &stages);
+        Ok(results)
+    }
+}
+
+/// Adds the 1-based `line` number, `source` and `inputHash` to each line's
+/// document. Pipelines that depend on input order sort on `line`.
+fn annotate(input: &str, source: &str, documents: Vec<Document>) -> Result<Vec<Document>> {
+    let lines = input.lines().count();
+    if documents.len() != lines {
+        bail!(
+            "expected a document for each of {} lines, got {}",
+            lines,
+            documents.len()
+        );
+    }
+    let hash = input::hash(input);
+    Ok(documents
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut document)| {
+            document.insert("line", i as i64 + 1);
+            document.insert("source", source);
+            document.insert("inputHash", &hash);
+            document
+        })
+        .collect())
+}
+
+/// An evaluator that knows the Rust equivalents of every `$function` the
+/// pipelines use.
+pub fn evaluator() -> eval::Evaluator {
+    eval::Evaluator::new().with_function(day3::PRIORITY, day3::priority)
+}
+
+pub const DEFAULT_URI: &str = "mongodb://localhost";
+pub const DEFAULT_DATABASE: &str = "aoc2022";
+pub const DEFAULT_COLLECTION_PREFIX: &str = "input";
+pub const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+/// Where the pipeline solvers load their input and what they leave behind.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// A connection string, which may carry credentials and TLS options.
+    pub uri: String,
+    pub database: String,
+    /// Day N's input is loaded into a collection named
+    /// `{collection_prefix}N_{suffix}`, where the suffix is unique to the run
+    /// unless the input is kept or reused.
+    pub collection_prefix: String,
+    /// Leave the loaded input in place after a run instead of dropping it, in
+    /// the collection [`Config::reuse`] would load it into, whose name is
+    /// printed.
+    pub keep_data: bool,
+    /// Load each input once into a collection whose suffix is a prefix of the
+    /// input's hash, and keep it for later runs of the same input.
+    pub reuse: bool,
+    /// Insert input in batches of at most this many documents.
+    pub batch_size: usize,
+    /// Insert batches one after another, in order, rather than concurrently.
+    pub ordered: bool,
+    /// Explain each aggregation and print how its stages ran.
+    pub explain: bool,
+    /// Let stages spill to disk, or leave it to the server's default if unset.
+    pub allow_disk_use: Option<bool>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            uri: DEFAULT_URI.to_string(),
+            database: DEFAULT_DATABASE.to_string(),
+            collection_prefix: DEFAULT_COLLECTION_PREFIX.to_string(),
+            keep_data: false,
+            reuse: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            ordered: true,
+            explain: false,
+            allow_disk_use: None,
+        }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Sets the configuration used by every later call. It can only be set once,
+/// and the default is used if it's never set.
+pub fn configure(config: Config) -> Result<()> {
+    CONFIG
+        .set(config)
+        .map_err(|_| anyhow!("MongoDB is already configured"))
+}
+
+fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+async fn available_() -> Result<()> {
+    let mut client_options = ClientOptions::parse(&config().uri).await?;
+    client_options.server_selection_timeout = Some(Duration::from_secs(1));
+    let client = Client::with_options(client_options)?;
+    client
+        .database("admin")
+        .run_command(doc! { "ping": 1 }, None)
+        .await?;
+    Ok(())
+}
+
+/// Whether a MongoDB server answers a ping.
+pub fn available() -> bool {
+    Runtime::new()
+        .map(|rt| rt.block_on(available_()).is_ok())
+        .unwrap_or(false)
+}
+
+async fn insert(
+    collection: &Collection<Document>,
+    documents: &[Document],
+) -> mongodb::error::Result<()> {
+    let config = config();
+    let options = InsertManyOptions::builder().ordered(config.ordered).build();
+    let batches = documents.chunks(config.batch_size.max(1));
+    if config.ordered {
+        for batch in batches {
+            collection.insert_many(batch, options.clone()).await?;
+        }
+    } else {
+        try_join_all(batches.map(|batch| collection.insert_many(batch, options.clone()))).await?;
+    }
+    Ok(())
+}
+
+/// The `NamespaceExists` error code returned by `renameCollection`.
+const NAMESPACE_EXISTS: i32 = 48;
+
+/// Loads `documents` into the collection `name` unless it already exists.
+/// They're loaded into a staging collection first and renamed into place, so
+/// concurrent runs never see a partly loaded collection; if another run gets
+/// there first, its copy is kept and the staging collection dropped.
+async fn load_once(
+    client: &Client,
+    database: &Database,
+    name: &str,
+    documents: &[Document],
+) -> Result<()> {
+    let existing = database
+        .list_collection_names(doc! { "name": name })
+        .await?;
+    if !existing.is_empty() {
+        return Ok(());
+    }
+    let staging = database.collection::<Document>(&format!("{}_{}", name, ObjectId::new()));
+    let loaded = async {
+        insert(&staging, documents).await?;
+        client
+            .database("admin")
+            .run_command(
+                doc! {
+                    "renameCollection": staging.namespace().to_string(),
+                    "to": format!("{}.{}", database.name(), name),
+                },
+                None,
+            )
+            .await
+    }
+    .await;
+    match loaded {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            staging.drop(None).await?;
+            match *e.kind {
+                ErrorKind::Command(CommandError {
+                    code: NAMESPACE_EXISTS,
+                    ..
+                }) => Ok(()),
+                _ => Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Prints how each stage of day `day`'s pipeline ran.
+fn report(day: u8, stages: &[explain::Stage]) {
+    eprint!("day {} pipeline\n{}", day, explain::summary(stages));
+}
+
+async fn run_pipeline(
+    day: u8,
+    database: &Database,
+    collection: &str,
+    pipeline: Vec<Document>,
+) -> Result<Vec<Document>> {
+    let config = config();
+    let input = database.collection::<Document>(collection);
+    let pipeline = resolve_input(pipeline, collection);
+    let options = AggregateOptions::builder()
+        .allow_disk_use(config.allow_disk_use)
+        .build();
+    let results = input
+        .aggregate(pipeline.clone(), options)
+        .await?
+        .try_collect()
+        .await?;
+    if config.explain && explain::writes(&pipeline) {
+        eprintln!("day {} pipeline writes, so it isn't explained", day);
+    } else if config.explain {
+        let mut command = doc! {
+            "aggregate": collection,
+            "pipeline": pipeline,
+            "cursor": {},
+        };
+        if let Some(allow_disk_use) = config.allow_disk_use {
+            command.insert("allowDiskUse", allow_disk_use);
+        }
+        let explained = database
+            .run_command(
+                doc! { "explain": command, "verbosity": "executionStats" },
+                None,
+            )
+            .await?;
+        report(day, &explain::stages(&explained));
+    }
+    Ok(results)
+}
+
+async fn aggregate_(
+    day: u8,
+    hash: &str,
+    documents: Vec<Document>,
+    pipeline: Vec<Document>,
+) -> Result<Vec<Document>> {
+    let config = config();
+    let client_options = ClientOptions::parse(&config.uri).await?;
+    let client = Client::with_options(client_options)?;
+
+    let database = client.database(&config.database);
+    let base = format!("{}{}", config.collection_prefix, day);
+
+    // Kept input goes where it can be found again: the collection named
+    // after its hash, which reuse loads once and later runs share.
+    if config.reuse || config.keep_data {
+        let name = format!("{}_{}", base, &hash[..16]);
+        load_once(&client, &database, &name, &documents).await?;
+        if config.keep_data {
+            eprintln!("day {} input kept in {}.{}", day, database.name(), name);
+        }
+        return run_pipeline(day, &database, &name, pipeline).await;
+    }
+
+    // A collection of its own, so concurrent runs of the same day don't
+    // clobber each other's input.
+    let input = database.collection::<Document>(&format!("{}_{}", base, ObjectId::new()));
+    let results = async {
+        insert(&input, &documents).await?;
+        run_pipeline(day, &database, input.name(), pipeline).await
+    }
+    .await;
+
+    let dropped = input.drop(None).await;
+    let results = results?;
+    dropped?;
+    Ok(results)
+}
+
+/// An integer from a pipeline, which may come back as a 32 or 64-bit integer,
+/// or as a whole double from `$divide` or `$function`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Integer(pub i64);
+
+impl From<Integer> for Answer {
+    fn from(n: Integer) -> Self {
+        Answer::Num(n.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Integer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IntegerVisitor;
+
+        impl<'de> Visitor<'de> for IntegerVisitor {
+            type Value = Integer;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer")
+            }
+
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<Integer, E> {
+                Ok(Integer(n))
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Integer, E> {
+                i64::try_from(n)
+                    .map(Integer)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(n), &self))
+            }
+
+            fn visit_f64<E: de::Error>(self, n: f64) -> Result<Integer, E> {
+                if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                    Ok(Integer(n as i64))
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Float(n), &self))
+                }
+            }
+        }
+
+        deserializer.deserialize_any(IntegerVisitor)
+    }
+}
+
+/// Deserializes the single document a pipeline produced. The pipelines
+/// produce none for empty input, whose answer is the default, 0 for an
+/// [`Integer`], as it is for the native solvers.
+pub fn single<T: DeserializeOwned + Default>(documents: &[Document]) -> Result<T> {
+    let document = match documents {
+        [] => return Ok(T::default()),
+        [document] => document,
+        _ => bail!("expected one result document, got {}", documents.len()),
+    };
+    let name = any::type_name::<T>()
+        .rsplit("::")
+        .next()
+        .unwrap_or_default();
+    bson::from_document(document.clone())
+        .map_err(|e| anyhow!("couldn't read {} from {}: {}", name, document, e))
+}
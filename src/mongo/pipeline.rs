@@ -0,0 +1,529 @@
+//! A typed builder for aggregation pipelines that compiles to the same BSON as
+//! the equivalent `doc!` literals.
+
+use mongodb::bson::{doc, Bson, Document};
+
+/// An aggregation expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr(Bson);
+
+impl From<Expr> for Bson {
+    fn from(expr: Expr) -> Self {
+        expr.0
+    }
+}
+
+impl From<Bson> for Expr {
+    fn from(value: Bson) -> Self {
+        Expr(value)
+    }
+}
+
+impl From<i32> for Expr {
+    fn from(n: i32) -> Self {
+        Expr(n.into())
+    }
+}
+
+impl From<i64> for Expr {
+    fn from(n: i64) -> Self {
+        Expr(n.into())
+    }
+}
+
+/// A string literal, wrapped in `$literal` if it would otherwise be read as
+/// a path. Use [`field`] or [`var`] to refer to values.
+impl From<&str> for Expr {
+    fn from(s: &str) -> Self {
+        if s.starts_with('$') {
+            op("$literal", s)
+        } else {
+            Expr(s.into())
+        }
+    }
+}
+
+fn op(name: &str, args: impl Into<Bson>) -> Expr {
+    let mut document = Document::new();
+    document.insert(name, args);
+    Expr(document.into())
+}
+
+fn args<const N: usize>(args: [Expr; N]) -> Bson {
+    Bson::Array(args.into_iter().map(Bson::from).collect())
+}
+
+fn list(items: impl IntoIterator<Item = Expr>) -> Bson {
+    Bson::Array(items.into_iter().map(Bson::from).collect())
+}
+
+/// The value of `path` in the current document.
+pub fn field(path: &str) -> Expr {
+    Expr(format!("${}", path).into())
+}
+
+/// The value of the variable `name`, such as `this` inside [`map`].
+pub fn var(name: &str) -> Expr {
+    Expr(format!("$${}", name).into())
+}
+
+/// An array of expressions.
+pub fn array<T: Into<Expr>>(items: impl IntoIterator<Item = T>) -> Expr {
+    Expr(list(items.into_iter().map(Into::into)))
+}
+
+/// A document whose fields are the values of `fields`.
+pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Expr)>) -> Expr {
+    Expr(Bson::Document(
+        fields
+            .into_iter()
+            .map(|(field, expr)| (field.to_string(), Bson::from(expr)))
+            .collect(),
+    ))
+}
+
+pub fn add(terms: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$add", list(terms))
+}
+
+pub fn multiply(terms: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$multiply", list(terms))
+}
+
+pub fn subtract(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$subtract", args([a.into(), b.into()]))
+}
+
+pub fn divide(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$divide", args([a.into(), b.into()]))
+}
+
+pub fn modulo(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$mod", args([a.into(), b.into()]))
+}
+
+pub fn eq(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$eq", args([a.into(), b.into()]))
+}
+
+pub fn lt(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$lt", args([a.into(), b.into()]))
+}
+
+pub fn lte(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$lte", args([a.into(), b.into()]))
+}
+
+pub fn gt(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$gt", args([a.into(), b.into()]))
+}
+
+pub fn gte(a: impl Into<Expr>, b: impl Into<Expr>) -> Expr {
+    op("$gte", args([a.into(), b.into()]))
+}
+
+pub fn and(terms: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$and", list(terms))
+}
+
+pub fn or(terms: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$or", list(terms))
+}
+
+pub fn cond(if_: impl Into<Expr>, then: impl Into<Expr>, else_: impl Into<Expr>) -> Expr {
+    op(
+        "$cond",
+        doc! {
+            "if": Bson::from(if_.into()),
+            "then": Bson::from(then.into()),
+            "else": Bson::from(else_.into()),
+        },
+    )
+}
+
+pub fn array_elem_at(array: impl Into<Expr>, index: impl Into<Expr>) -> Expr {
+    op("$arrayElemAt", args([array.into(), index.into()]))
+}
+
+pub fn index_of_array(array: impl Into<Expr>, value: impl Into<Expr>) -> Expr {
+    op("$indexOfArray", args([array.into(), value.into()]))
+}
+
+pub fn first(array: impl Into<Expr>) -> Expr {
+    op("$first", array.into())
+}
+
+/// The sum of an array, or of a field across a group when used in [`Group`].
+pub fn sum(array: impl Into<Expr>) -> Expr {
+    op("$sum", array.into())
+}
+
+pub fn range(start: impl Into<Expr>, end: impl Into<Expr>) -> Expr {
+    op("$range", args([start.into(), end.into()]))
+}
+
+/// Applies `in_` to each element of `input`, which it sees as [`var`]`("this")`.
+pub fn map(input: impl Into<Expr>, in_: impl Into<Expr>) -> Expr {
+    op(
+        "$map",
+        doc! {
+            "input": Bson::from(input.into()),
+            "in": Bson::from(in_.into()),
+        },
+    )
+}
+
+/// Folds `in_` over `input`, which sees the accumulator as [`var`]`("value")`
+/// and each element as [`var`]`("this")`.
+pub fn reduce(input: impl Into<Expr>, initial: impl Into<Expr>, in_: impl Into<Expr>) -> Expr {
+    op(
+        "$reduce",
+        doc! {
+            "input": Bson::from(input.into()),
+            "initialValue": Bson::from(initial.into()),
+            "in": Bson::from(in_.into()),
+        },
+    )
+}
+
+pub fn concat_arrays(arrays: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$concatArrays", list(arrays))
+}
+
+pub fn set_intersection(sets: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$setIntersection", list(sets))
+}
+
+pub fn set_union(sets: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$setUnion", list(sets))
+}
+
+pub fn size(array: impl Into<Expr>) -> Expr {
+    op("$size", array.into())
+}
+
+/// Up to `n` elements of `array` from `position`.
+pub fn slice(array: impl Into<Expr>, position: impl Into<Expr>, n: impl Into<Expr>) -> Expr {
+    op("$slice", args([array.into(), position.into(), n.into()]))
+}
+
+/// The first `n` elements of `array`, or the last `-n` if `n` is negative.
+pub fn take(array: impl Into<Expr>, n: impl Into<Expr>) -> Expr {
+    op("$slice", args([array.into(), n.into()]))
+}
+
+pub fn concat(strings: impl IntoIterator<Item = Expr>) -> Expr {
+    op("$concat", list(strings))
+}
+
+pub fn str_len_cp(s: impl Into<Expr>) -> Expr {
+    op("$strLenCP", s.into())
+}
+
+pub fn substr_cp(s: impl Into<Expr>, start: impl Into<Expr>, len: impl Into<Expr>) -> Expr {
+    op("$substrCP", args([s.into(), start.into(), len.into()]))
+}
+
+pub fn str_len_bytes(s: impl Into<Expr>) -> Expr {
+    op("$strLenBytes", s.into())
+}
+
+/// `len` bytes of `s` from `start`, or the rest of `s` if `len` is negative.
+pub fn substr_bytes(s: impl Into<Expr>, start: impl Into<Expr>, len: impl Into<Expr>) -> Expr {
+    op("$substrBytes", args([s.into(), start.into(), len.into()]))
+}
+
+/// The code point index of `substring` in `s`, or -1.
+pub fn index_of_cp(s: impl Into<Expr>, substring: impl Into<Expr>) -> Expr {
+    op("$indexOfCP", args([s.into(), substring.into()]))
+}
+
+pub fn split(s: impl Into<Expr>, separator: &str) -> Expr {
+    op("$split", args([s.into(), separator.into()]))
+}
+
+pub fn to_int(value: impl Into<Expr>) -> Expr {
+    op("$toInt", value.into())
+}
+
+/// Calls the JavaScript function `body` with `args`.
+pub fn function(body: &str, args: impl IntoIterator<Item = Expr>) -> Expr {
+    op(
+        "$function",
+        doc! {
+            "body": body,
+            "args": list(args),
+            "lang": "js",
+        },
+    )
+}
+
+/// The single-byte characters of `s`, as an array of strings.
+pub fn chars(s: impl Into<Expr>) -> Expr {
+    let s = s.into();
+    map(
+        range(0, str_len_bytes(s.clone())),
+        substr_bytes(s, var("this"), 1),
+    )
+}
+
+/// The characters of `s`, whatever their encoded length, as an array of
+/// strings.
+pub fn code_points(s: impl Into<Expr>) -> Expr {
+    let s = s.into();
+    map(
+        range(0, str_len_cp(s.clone())),
+        substr_cp(s, var("this"), 1),
+    )
+}
+
+/// The `index`th piece of `s` split on `separator`.
+pub fn split_at(s: impl Into<Expr>, separator: &str, index: i32) -> Expr {
+    array_elem_at(split(s, separator), index)
+}
+
+fn sort(keys: &[(&str, i32)]) -> Document {
+    keys.iter()
+        .map(|(field, direction)| (field.to_string(), Bson::from(*direction)))
+        .collect()
+}
+
+/// One end of a `documents` window, relative to the current document.
+#[derive(Clone, Copy, Debug)]
+pub enum Bound {
+    Unbounded,
+    Current,
+    Offset(i32),
+}
+
+impl From<Bound> for Bson {
+    fn from(bound: Bound) -> Self {
+        match bound {
+            Bound::Unbounded => "unbounded".into(),
+            Bound::Current => "current".into(),
+            Bound::Offset(n) => n.into(),
+        }
+    }
+}
+
+/// A `$setWindowFields` output field.
+#[derive(Clone, Debug)]
+pub struct Window(Document);
+
+impl Window {
+    pub fn sum(expr: impl Into<Expr>) -> Self {
+        Window(doc! { "$sum": Bson::from(expr.into()) })
+    }
+
+    pub fn count() -> Self {
+        Window(doc! { "$count": {} })
+    }
+
+    pub fn push(expr: impl Into<Expr>) -> Self {
+        Window(doc! { "$push": Bson::from(expr.into()) })
+    }
+
+    pub fn add_to_set(expr: impl Into<Expr>) -> Self {
+        Window(doc! { "$addToSet": Bson::from(expr.into()) })
+    }
+
+    /// The document's 1-based position in the partition's sort order, with
+    /// ties sharing the lowest. Takes no window.
+    pub fn rank() -> Self {
+        Window(doc! { "$rank": {} })
+    }
+
+    pub fn documents(mut self, lower: Bound, upper: Bound) -> Self {
+        self.0.insert(
+            "window",
+            doc! { "documents": [Bson::from(lower), Bson::from(upper)] },
+        );
+        self
+    }
+}
+
+/// A `$setWindowFields` stage.
+#[derive(Clone, Debug, Default)]
+pub struct SetWindowFields {
+    partition_by: Option<Expr>,
+    sort_by: Document,
+    output: Document,
+}
+
+impl SetWindowFields {
+    pub fn new() -> Self {
+        SetWindowFields::default()
+    }
+
+    pub fn partition_by(mut self, expr: impl Into<Expr>) -> Self {
+        self.partition_by = Some(expr.into());
+        self
+    }
+
+    pub fn sort_by(mut self, keys: &[(&str, i32)]) -> Self {
+        self.sort_by = sort(keys);
+        self
+    }
+
+    pub fn output(mut self, field: &str, window: Window) -> Self {
+        self.output.insert(field, window.0);
+        self
+    }
+}
+
+/// A `$group` stage.
+#[derive(Clone, Debug)]
+pub struct Group(Document);
+
+impl Group {
+    pub fn by(id: impl Into<Expr>) -> Self {
+        Group(doc! { "_id": Bson::from(id.into()) })
+    }
+
+    pub fn sum(mut self, field: &str, expr: impl Into<Expr>) -> Self {
+        self.0.insert(field, Bson::from(sum(expr)));
+        self
+    }
+
+    pub fn min(mut self, field: &str, expr: impl Into<Expr>) -> Self {
+        self.0.insert(field, Bson::from(op("$min", expr.into())));
+        self
+    }
+
+    pub fn max(mut self, field: &str, expr: impl Into<Expr>) -> Self {
+        self.0.insert(field, Bson::from(op("$max", expr.into())));
+        self
+    }
+
+    pub fn push(mut self, field: &str, expr: impl Into<Expr>) -> Self {
+        self.0.insert(field, Bson::from(op("$push", expr.into())));
+        self
+    }
+
+    /// The first `n` values of `output` in the group ordered by `sort_by`.
+    pub fn top_n(
+        mut self,
+        field: &str,
+        output: impl Into<Expr>,
+        sort_by: &[(&str, i32)],
+        n: i32,
+    ) -> Self {
+        self.0.insert(
+            field,
+            doc! {
+                "$topN": {
+                    "output": Bson::from(output.into()),
+                    "sortBy": sort(sort_by),
+                    "n": n,
+                },
+            },
+        );
+        self
+    }
+}
+
+/// A sequence of stages, built up in order.
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline(Vec<Document>);
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    pub fn set_window_fields(mut self, stage: SetWindowFields) -> Self {
+        let mut spec = Document::new();
+        if let Some(partition_by) = stage.partition_by {
+            spec.insert("partitionBy", Bson::from(partition_by));
+        }
+        spec.insert("sortBy", stage.sort_by);
+        spec.insert("output", stage.output);
+        self.0.push(doc! { "$setWindowFields": spec });
+        self
+    }
+
+    /// Adds to each document the array `as_` of every document in `from`
+    /// reachable by following `connect_from` values to matching
+    /// `connect_to` values, starting from `start_with`.
+    pub fn graph_lookup(
+        mut self,
+        from: &str,
+        start_with: impl Into<Expr>,
+        connect_from: &str,
+        connect_to: &str,
+        as_: &str,
+    ) -> Self {
+        self.0.push(doc! {
+            "$graphLookup": {
+                "from": from,
+                "startWith": Bson::from(start_with.into()),
+                "connectFromField": connect_from,
+                "connectToField": connect_to,
+                "as": as_,
+            }
+        });
+        self
+    }
+
+    /// Keeps the documents for which `expr` is truthy.
+    pub fn match_expr(mut self, expr: impl Into<Expr>) -> Self {
+        self.0
+            .push(doc! { "$match": { "$expr": Bson::from(expr.into()) } });
+        self
+    }
+
+    pub fn group(mut self, group: Group) -> Self {
+        self.0.push(doc! { "$group": group.0 });
+        self
+    }
+
+    pub fn sort(mut self, keys: &[(&str, i32)]) -> Self {
+        self.0.push(doc! { "$sort": sort(keys) });
+        self
+    }
+
+    pub fn limit(mut self, n: i64) -> Self {
+        self.0.push(doc! { "$limit": n });
+        self
+    }
+
+    /// Replaces each document with one per element of its array `field`.
+    pub fn unwind(mut self, field: &str) -> Self {
+        self.0.push(doc! { "$unwind": format!("${}", field) });
+        self
+    }
+
+    /// Like [`unwind`](Self::unwind), also storing each element's index in
+    /// its array as `index`.
+    pub fn unwind_with_index(mut self, field: &str, index: &str) -> Self {
+        self.0.push(doc! {
+            "$unwind": { "path": format!("${}", field), "includeArrayIndex": index }
+        });
+        self
+    }
+
+    /// Writes the results to the collection `into` in the same database,
+    /// replacing documents with the same `_id`.
+    pub fn merge(mut self, into: &str) -> Self {
+        self.0.push(doc! {
+            "$merge": { "into": into, "whenMatched": "replace", "whenNotMatched": "insert" }
+        });
+        self
+    }
+
+    /// Replaces each document with `_id` and the computed `fields`.
+    pub fn project<'a>(mut self, fields: impl IntoIterator<Item = (&'a str, Expr)>) -> Self {
+        let spec = fields
+            .into_iter()
+            .map(|(field, expr)| (field.to_string(), Bson::from(expr)))
+            .collect::<Document>();
+        self.0.push(doc! { "$project": spec });
+        self
+    }
+}
+
+impl From<Pipeline> for Vec<Document> {
+    fn from(pipeline: Pipeline) -> Self {
+        pipeline.0
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, bail, Result};
+use clap::ValueEnum;
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, DateTime, Document},
+    options::{ClientOptions, FindOptions},
+    Client, Collection, IndexModel,
+};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use super::config;
+use crate::{Answer, Backend};
+
+/// The collection in the configured database that runs are written to.
+pub const COLLECTION: &str = "results";
+
+/// One part run by one backend.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    /// The answer, or the error message if the solver failed.
+    pub result: Result<Answer, String>,
+    pub backend: Backend,
+    pub elapsed: Duration,
+    pub input_hash: Option<String>,
+    /// The commit the solvers were built from, if known.
+    pub commit: Option<String>,
+    pub timestamp: DateTime,
+}
+
+impl Record {
+    pub fn to_document(&self) -> Document {
+        let mut document = doc! {
+            "day": i32::from(self.day),
+            "part": i32::from(self.part),
+            "backend": self.backend.name(),
+            "elapsedSecs": self.elapsed.as_secs_f64(),
+            "inputHash": self.input_hash.as_deref().map_or(Bson::Null, Bson::from),
+            "commit": self.commit.as_deref().map_or(Bson::Null, Bson::from),
+            "timestamp": self.timestamp,
+        };
+        match &self.result {
+            Ok(Answer::Num(n)) => document.insert("answer", *n),
+            Ok(Answer::Text(s)) => document.insert("answer", s),
+            Err(e) => document.insert("error", e),
+        };
+        document
+    }
+
+    pub fn from_document(document: &Document) -> Result<Self> {
+        let small = |field: &str| -> Result<u8> { Ok(u8::try_from(document.get_i32(field)?)?) };
+        let optional = |field: &str| document.get_str(field).ok().map(str::to_string);
+        let result = match (document.get("answer"), document.get_str("error")) {
+            (Some(Bson::Int64(n)), _) => Ok(Answer::Num(*n)),
+            (Some(Bson::Int32(n)), _) => Ok(Answer::Num((*n).into())),
+            (Some(Bson::String(s)), _) => Ok(Answer::Text(s.clone())),
+            (None, Ok(e)) => Err(e.to_string()),
+            _ => bail!("no answer or error in {}", document),
+        };
+        let part = small("part")?;
+        if !(1..=2).contains(&part) {
+            bail!("no part {} in {}", part, document);
+        }
+        let backend = document.get_str("backend")?;
+        Ok(Record {
+            day: small("day")?,
+            part,
+            result,
+            backend: Backend::from_str(backend, false)
+                .map_err(|_| anyhow!("unknown backend {}", backend))?,
+            elapsed: Duration::try_from_secs_f64(document.get_f64("elapsedSecs")?)?,
+            input_hash: optional("inputHash"),
+            commit: optional("commit"),
+            timestamp: *document.get_datetime("timestamp")?,
+        })
+    }
+}
+
+async fn collection() -> Result<Collection<Document>> {
+    let config = config();
+    let client = Client::with_options(ClientOptions::parse(&config.uri).await?)?;
+    Ok(client.database(&config.database).collection(COLLECTION))
+}
+
+async fn save_(records: &[Record]) -> Result<()> {
+    let results = collection().await?;
+    results
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "day": 1, "part": 1, "timestamp": -1 })
+                .build(),
+            None,
+        )
+        .await?;
+    results
+        .insert_many(records.iter().map(Record::to_document), None)
+        .await?;
+    Ok(())
+}
+
+/// Appends `records` to the results collection.
+pub fn save(records: &[Record]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    Runtime::new()?.block_on(save_(records))
+}
+
+async fn history_(day: u8, part: Option<u8>, limit: i64) -> Result<Vec<Record>> {
+    let mut filter = doc! { "day": i32::from(day) };
+    if let Some(part) = part {
+        filter.insert("part", i32::from(part));
+    }
+    let options = FindOptions::builder()
+        .sort(doc! { "timestamp": -1 })
+        .limit(limit)
+        .build();
+    let documents: Vec<Document> = collection()
+        .await?
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+    documents.iter().rev().map(Record::from_document).collect()
+}
+
+/// The latest `limit` records for `day`, and `part` if given, oldest first.
+pub fn history(day: u8, part: Option<u8>, limit: i64) -> Result<Vec<Record>> {
+    Runtime::new()?.block_on(history_(day, part, limit))
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
+
+/// Blank lines, which separate elves, become nulls.
+fn parse<R: Read>(reader: R) -> Result<Vec<[Value; 1]>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            let l = l?;
+            Ok([if l.is_empty() {
+                Value::Null
+            } else {
+                Value::Integer(l.parse()?)
+            }])
+        })
+        .collect()
+}
+
+/// Numbers each elf by the blank lines before it and totals their calories.
+const TOTALS: &str = "
+    WITH elves AS (
+        SELECT calories, SUM(calories IS NULL) OVER (ORDER BY line) AS elf
+        FROM input
+    ), totals AS (
+        SELECT SUM(calories) AS total
+        FROM elves
+        WHERE calories IS NOT NULL
+        GROUP BY elf
+    )";
+
+fn top(input: &str, n: i64) -> Result<Answer> {
+    let connection = super::load(["calories INTEGER"], parse(input.as_bytes())?)?;
+    super::answer(
+        &connection,
+        &format!(
+            "{} SELECT SUM(total) FROM (SELECT total FROM totals ORDER BY total DESC LIMIT {})",
+            TOTALS, n
+        ),
+    )
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    top(input, 1)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    top(input, 3)
+}
@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, types::Value, Connection, OptionalExtension};
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    day2::{self, Outcome, Rules},
+    Answer,
+};
+
+fn parse<R: Read>(reader: R) -> Result<Vec<[Value; 2]>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            let l = l?;
+            let (opp, self_) = l
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("Couldn't parse line"))?;
+            Ok([Value::Text(opp.to_owned()), Value::Text(self_.to_owned())])
+        })
+        .collect()
+}
+
+/// Adds the tables `shapes`, `beats` and `outcomes` describing `rules`, with
+/// shapes and outcomes numbered from 0 and outcomes in lose, draw, win order.
+fn load_rules(connection: &Connection, rules: &Rules) -> Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE shapes (shape INTEGER PRIMARY KEY, name TEXT, opponent TEXT, player TEXT, score INTEGER);
+         CREATE TABLE beats (winner TEXT, loser TEXT);
+         CREATE TABLE outcomes (outcome INTEGER PRIMARY KEY, player TEXT, score INTEGER);",
+    )?;
+    for (i, shape) in rules.shapes.iter().enumerate() {
+        connection.execute(
+            "INSERT INTO shapes VALUES (?, ?, ?, ?, ?)",
+            params![
+                i as i64,
+                shape.name,
+                shape.opponent,
+                shape.player,
+                shape.score
+            ],
+        )?;
+        for beaten in &shape.beats {
+            connection.execute(
+                "INSERT INTO beats VALUES (?, ?)",
+                params![shape.name, beaten],
+            )?;
+        }
+    }
+    for outcome in Outcome::ALL {
+        let rule = rules.outcome_rule(outcome);
+        connection.execute(
+            "INSERT INTO outcomes VALUES (?, ?, ?)",
+            params![outcome as i32, rule.player, rule.score],
+        )?;
+    }
+    Ok(())
+}
+
+/// The `outcome` of playing every shape `s` against every opponent shape
+/// `o`, and each round's opponent shape `o` and strategy symbol `self`.
+const ROUNDS: &str = "
+    WITH results AS (
+        SELECT s.shape AS s, o.shape AS o,
+            CASE
+                WHEN s.shape = o.shape THEN 1
+                WHEN EXISTS (SELECT 1 FROM beats WHERE winner = s.name AND loser = o.name) THEN 2
+                ELSE 0
+            END AS outcome
+        FROM shapes s, shapes o
+    ), rounds AS (
+        SELECT shapes.shape AS o, input.self
+        FROM input JOIN shapes ON shapes.opponent = input.opp
+    )";
+
+/// Fails like the native solvers on the first symbol that isn't in the rules,
+/// checking the opponent's before the strategy's, which are looked up in the
+/// `player` column of `strategies`.
+fn check_symbols(connection: &Connection, strategies: &str) -> Result<()> {
+    let unknown = |column: &str, table: &str, key: &str| {
+        connection
+            .query_row(
+                &format!(
+                    "SELECT input.{column}
+                     FROM input LEFT JOIN {table} ON {table}.{key} = input.{column}
+                     WHERE {table}.{key} IS NULL
+                     ORDER BY input.line
+                     LIMIT 1"
+                ),
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+    };
+    if let Some(symbol) = unknown("opp", "shapes", "opponent")? {
+        return Err(day2::unknown_opponent(&symbol));
+    }
+    if let Some(symbol) = unknown("self", strategies, "player")? {
+        return Err(day2::unknown_strategy(&symbol));
+    }
+    Ok(())
+}
+
+fn total_score(input: &str, rules: &Rules, strategies: &str, query: &str) -> Result<Answer> {
+    let connection = super::load(["opp TEXT", "self TEXT"], parse(input.as_bytes())?)?;
+    load_rules(&connection, rules)?;
+    check_symbols(&connection, strategies)?;
+    super::answer(&connection, &format!("{} {}", ROUNDS, query))
+}
+
+pub fn part1(input: &str, rules: &Rules) -> Result<Answer> {
+    total_score(
+        input,
+        rules,
+        "shapes",
+        "SELECT SUM(shapes.score + outcomes.score)
+         FROM rounds
+         JOIN shapes ON shapes.player = rounds.self
+         JOIN results ON results.s = shapes.shape AND results.o = rounds.o
+         JOIN outcomes USING (outcome)",
+    )
+}
+
+/// The highest scoring shape that gets each round's outcome is played.
+pub fn part2(input: &str, rules: &Rules) -> Result<Answer> {
+    total_score(
+        input,
+        rules,
+        "outcomes",
+        "SELECT SUM(outcomes.score + (
+             SELECT MAX(shapes.score)
+             FROM results JOIN shapes ON shapes.shape = results.s
+             WHERE results.o = rounds.o AND results.outcome = outcomes.outcome
+         ))
+         FROM rounds JOIN outcomes ON outcomes.player = rounds.self",
+    )
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{
+    day3::{self, Config, Problem},
+    Answer,
+};
+
+fn parse<R: Read>(reader: R) -> Result<Vec<[Value; 1]>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| Ok([Value::Text(l?)]))
+        .collect()
+}
+
+/// One row per item, with its position in the rucksack and the rucksack's
+/// 0-based index.
+const ITEMS: &str = "
+    WITH RECURSIVE positions(i) AS (
+        SELECT 1
+        UNION ALL
+        SELECT i + 1 FROM positions WHERE i < (SELECT MAX(length(items)) FROM input)
+    ), items AS (
+        SELECT
+            line - 1 AS sack,
+            i,
+            i <= length(items) / 2 AS first_half,
+            substr(items, i, 1) AS item
+        FROM input JOIN positions ON i <= length(items)
+    )";
+
+/// Scores each of `units`, the rucksacks or groups with their `first` and
+/// `last` lines and any `size_problem`, by the items `shared` by each
+/// `unit`. The alphabet is the first parameter.
+fn priority_sum(input: &str, alphabet: &str, units: &str, shared: &str) -> Result<Answer> {
+    let connection = super::load(["items TEXT"], parse(input.as_bytes())?)?;
+    let mut statement = connection.prepare(&format!(
+        "{}, units AS ({}), shared AS ({})
+         SELECT first, last,
+             CASE
+                 WHEN size_problem IS NOT NULL THEN size_problem
+                 WHEN COUNT(item) = 0 THEN '{}'
+                 WHEN COUNT(item) > 1 THEN '{}'
+                 WHEN instr(?1, MAX(item)) = 0
+                     THEN 'common item ' || MAX(item) || ' is not in the alphabet'
+             END,
+             COALESCE(SUM(instr(?1, item)), 0)
+         FROM units LEFT JOIN shared USING (unit)
+         GROUP BY unit
+         ORDER BY first",
+        ITEMS,
+        units,
+        shared,
+        day3::NO_COMMON_ITEM,
+        day3::SEVERAL_COMMON_ITEMS
+    ))?;
+    let mut sum = 0;
+    let mut problems = vec![];
+    let mut rows = statement.query([alphabet])?;
+    while let Some(row) = rows.next()? {
+        match row.get::<_, Option<String>>(2)? {
+            Some(message) => problems.push(Problem {
+                first: row.get(0)?,
+                last: row.get(1)?,
+                message,
+            }),
+            None => sum += row.get::<_, i64>(3)?,
+        }
+    }
+    day3::check(&problems)?;
+    Ok(sum.into())
+}
+
+pub fn part1(input: &str, config: &Config) -> Result<Answer> {
+    priority_sum(
+        input,
+        &config.alphabet,
+        &format!(
+            "SELECT line - 1 AS unit, line AS first, line AS last,
+                 CASE WHEN length(items) % 2 = 1 THEN '{}' END AS size_problem
+             FROM input",
+            day3::ODD
+        ),
+        "SELECT DISTINCT a.sack AS unit, a.item
+         FROM items a JOIN items b ON a.sack = b.sack AND a.item = b.item
+         WHERE a.first_half AND NOT b.first_half",
+    )
+}
+
+pub fn part2(input: &str, config: &Config) -> Result<Answer> {
+    let n = config.group_size;
+    priority_sum(
+        input,
+        &config.alphabet,
+        &format!(
+            "SELECT (line - 1) / {n} AS unit, MIN(line) AS first, MAX(line) AS last,
+                 CASE WHEN COUNT(*) < {n} THEN '{}' END AS size_problem
+             FROM input
+             GROUP BY unit",
+            day3::short_group(n),
+            n = n
+        ),
+        &format!(
+            "SELECT sack / {n} AS unit, item
+             FROM items
+             GROUP BY sack / {n}, item
+             HAVING COUNT(DISTINCT sack) = {n}",
+            n = n
+        ),
+    )
+}
@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use rusqlite::types::Value;
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Answer;
+
+fn parse<R: Read>(reader: R) -> Result<Vec<[Value; 4]>> {
+    BufReader::new(reader)
+        .lines()
+        .map(|l| {
+            let l = l?;
+            let bounds = l
+                .split([',', '-'])
+                .map(|n| Ok(Value::Integer(n.parse()?)))
+                .collect::<Result<Vec<Value>>>()?;
+            bounds
+                .try_into()
+                .map_err(|_| anyhow!("Couldn't parse line {}", l))
+        })
+        .collect()
+}
+
+fn count(input: &str, condition: &str) -> Result<Answer> {
+    let connection = super::load(
+        ["s1 INTEGER", "e1 INTEGER", "s2 INTEGER", "e2 INTEGER"],
+        parse(input.as_bytes())?,
+    )?;
+    super::answer(
+        &connection,
+        &format!("SELECT COUNT(*) FROM input WHERE {}", condition),
+    )
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    count(input, "(s1 <= s2 AND e2 <= e1) OR (s2 <= s1 AND e1 <= e2)")
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    count(input, "s1 <= e2 AND s2 <= e1")
+}
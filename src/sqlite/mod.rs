@@ -0,0 +1,78 @@
+//! Solvers that load each line of the input into a table in an in-memory
+//! SQLite database and answer with SQL.
+
+use anyhow::Result;
+use rusqlite::{params_from_iter, types::Value, Connection};
+
+use crate::{Answer, Implementation};
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+
+/// The days with SQL solvers.
+pub const DAYS: [Implementation; 4] = [
+    (
+        1,
+        |input, _| day1::part1(input),
+        |input, _| day1::part2(input),
+    ),
+    (
+        2,
+        |input, context| day2::part1(input, &context.rules),
+        |input, context| day2::part2(input, &context.rules),
+    ),
+    (
+        3,
+        |input, context| day3::part1(input, &context.rucksacks),
+        |input, context| day3::part2(input, &context.rucksacks),
+    ),
+    (
+        4,
+        |input, _| day4::part1(input),
+        |input, _| day4::part2(input),
+    ),
+];
+
+/// A database with one row per line of input in the table `input`, numbered
+/// from 1 in `line`, and the given `columns`, such as `"calories INTEGER"`.
+fn load<const N: usize>(columns: [&str; N], rows: Vec<[Value; N]>) -> Result<Connection> {
+    let mut connection = Connection::open_in_memory()?;
+    connection.execute(
+        &format!(
+            "CREATE TABLE input (line INTEGER PRIMARY KEY, {})",
+            columns.join(", ")
+        ),
+        [],
+    )?;
+    let names: Vec<&str> = columns
+        .iter()
+        .map(|column| column.split_whitespace().next().unwrap_or_default())
+        .collect();
+    let transaction = connection.transaction()?;
+    {
+        let mut insert = transaction.prepare(&format!(
+            "INSERT INTO input (line, {}) VALUES (?{})",
+            names.join(", "),
+            ", ?".repeat(N)
+        ))?;
+        for (i, row) in rows.into_iter().enumerate() {
+            insert.execute(params_from_iter(
+                std::iter::once(Value::Integer(i as i64 + 1)).chain(row),
+            ))?;
+        }
+    }
+    transaction.commit()?;
+    Ok(connection)
+}
+
+/// Runs `sql`, which must produce a single integer. A `SUM` over no rows is
+/// NULL, which is the answer 0 for empty input, as it is for the native
+/// solvers.
+fn answer(connection: &Connection, sql: &str) -> Result<Answer> {
+    Ok(connection
+        .query_row(sql, [], |row| row.get::<_, Option<i64>>(0))?
+        .unwrap_or(0)
+        .into())
+}
@@ -0,0 +1,79 @@
+use anyhow::anyhow;
+use aoc_2022::{
+    answers::{Answers, Status},
+    Answer,
+};
+use std::{env, fs};
+
+#[test]
+fn round_trip() {
+    let path = env::temp_dir().join(format!("aoc-answers-{}.toml", std::process::id()));
+    let mut answers = Answers::default();
+    answers.insert(1, 1, Answer::Num(24000));
+    answers.insert(10, 2, Answer::Text("##..\n#..#".to_string()));
+    answers.save(&path).unwrap();
+
+    let loaded = Answers::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.get(1, 1), Some(&Answer::Num(24000)));
+    assert_eq!(
+        loaded.get(10, 2),
+        Some(&Answer::Text("##..\n#..#".to_string()))
+    );
+    assert_eq!(loaded.get(1, 2), None);
+}
+
+#[test]
+fn check() {
+    let mut answers = Answers::default();
+    answers.insert(1, 1, Answer::Num(24000));
+
+    assert_eq!(answers.check(1, 1, &Ok(Answer::Num(24000))), Status::Ok);
+    assert_eq!(
+        answers.check(1, 1, &Ok(Answer::Num(1))),
+        Status::Mismatch(Answer::Num(24000))
+    );
+    assert_eq!(
+        answers.check(1, 1, &Err(anyhow!("oops"))),
+        Status::Failed(Answer::Num(24000))
+    );
+    assert_eq!(answers.check(1, 2, &Ok(Answer::Num(1))), Status::New);
+    assert_eq!(answers.check(1, 2, &Err(anyhow!("oops"))), Status::Missing);
+}
+
+#[test]
+fn save_keeps_comments() {
+    let path = env::temp_dir().join(format!("aoc-answers-comments-{}.toml", std::process::id()));
+    fs::write(
+        &path,
+        "# Known answers.\n[day1]\n# The sum.\npart1 = 1\npart2 = 2\n",
+    )
+    .unwrap();
+    let mut answers = Answers::load(&path).unwrap();
+    answers.insert(1, 1, Answer::Num(3));
+    answers.insert(1, 2, Answer::Num(2));
+    answers.insert(2, 1, Answer::Text("CMZ".to_string()));
+    answers.save(&path).unwrap();
+
+    let saved = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        saved,
+        "# Known answers.\n[day1]\n# The sum.\npart1 = 3\npart2 = 2\n\n[day2]\npart1 = \"CMZ\"\n"
+    );
+}
+
+#[test]
+fn skip() {
+    let path = env::temp_dir().join(format!("aoc-answers-skip-{}.toml", std::process::id()));
+    fs::write(&path, "[day16]\npart1 = 1651\nskip = [2]\n").unwrap();
+    let answers = Answers::load(&path).unwrap();
+    fs::write(&path, "[day16]\nskip = [3]\n").unwrap();
+    let error = Answers::load(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(answers.get(16, 1), Some(&Answer::Num(1651)));
+    assert!(answers.is_skipped(16, 2));
+    assert!(!answers.is_skipped(16, 1));
+    assert_eq!(error.to_string(), "day16.skip has a part that isn't 1 or 2");
+}
@@ -0,0 +1,89 @@
+use aoc_2022::{
+    mongo::{self, Integer},
+    solver, sqlite, Backend, Context, Implementation,
+};
+use mongodb::bson::doc;
+use serde::Deserialize;
+
+mod common;
+
+/// Checks that `backend` gives the native answers for the `days` it
+/// implements, on their examples and on empty input, failing where the native
+/// solvers fail.
+fn matches_native(backend: Backend, days: &[Implementation]) {
+    for &(day, ..) in days {
+        for (name, input) in [("example", common::example(day)), ("empty", String::new())] {
+            for part in 1..=2 {
+                let native = solver(day, part, Backend::Native).unwrap();
+                let other = solver(day, part, backend).unwrap();
+                let context = Context::default();
+                let message = format!("day {} part {} on {} input", day, part, name);
+                match native(&input, &context) {
+                    Ok(expected) => {
+                        assert_eq!(other(&input, &context).unwrap(), expected, "{}", message)
+                    }
+                    Err(_) => assert!(other(&input, &context).is_err(), "{}", message),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mongo_offline_matches_native() {
+    matches_native(Backend::MongoOffline, &mongo::OFFLINE_DAYS);
+}
+
+#[test]
+fn sqlite_matches_native() {
+    matches_native(Backend::Sqlite, &sqlite::DAYS);
+}
+
+/// Runs only when a MongoDB server is reachable, since the pipelines need one.
+#[test]
+fn mongo_matches_native() {
+    if !mongo::available() {
+        eprintln!("MongoDB isn't reachable, skipping");
+        return;
+    }
+    matches_native(Backend::Mongo, &mongo::DAYS);
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Total {
+    total_score: Integer,
+}
+
+#[test]
+fn typed_results() {
+    let total: Total = mongo::single(&[doc! { "_id": 0, "totalScore": 15.0 }]).unwrap();
+    assert_eq!(total.total_score, Integer(15));
+
+    let error = mongo::single::<Total>(&[doc! { "_id": 0 }]).unwrap_err();
+    assert!(
+        error.to_string().contains("missing field `totalScore`"),
+        "{}",
+        error
+    );
+
+    let error = mongo::single::<Total>(&[doc! { "totalScore": "15" }]).unwrap_err();
+    assert!(
+        error.to_string().contains("expected an integer"),
+        "{}",
+        error
+    );
+
+    let error = mongo::single::<Total>(&[doc! { "totalScore": 1.5 }]).unwrap_err();
+    assert!(
+        error.to_string().contains("expected an integer"),
+        "{}",
+        error
+    );
+
+    let total: Total = mongo::single(&[]).unwrap();
+    assert_eq!(total.total_score, Integer(0));
+
+    let error = mongo::single::<Total>(&[doc! {}, doc! {}]).unwrap_err();
+    assert!(error.to_string().contains("got 2"), "{}", error);
+}
@@ -0,0 +1,61 @@
+use aoc_2022::{
+    mongo::{self, Capabilities},
+    solver, Backend, Context,
+};
+
+mod common;
+
+/// Checks that the offline pipelines give the native answers when run as if
+/// on a server with only `capabilities`.
+fn matches_native(capabilities: Capabilities) {
+    let context = Context {
+        emulated: capabilities,
+        ..Context::default()
+    };
+    for &(day, ..) in &mongo::OFFLINE_DAYS {
+        let input = common::example(day);
+        for part in 1..=2 {
+            let native = solver(day, part, Backend::Native).unwrap();
+            let offline = solver(day, part, Backend::MongoOffline).unwrap();
+            assert_eq!(
+                offline(&input, &context).unwrap(),
+                native(&input, &context).unwrap(),
+                "day {} part {} with {:?}",
+                day,
+                part,
+                capabilities
+            );
+        }
+    }
+}
+
+#[test]
+fn mongo_4_4_without_javascript() {
+    matches_native(Capabilities::for_version((4, 4), false).unwrap());
+}
+
+#[test]
+fn mongo_4_4() {
+    matches_native(Capabilities::for_version((4, 4), true).unwrap());
+}
+
+#[test]
+fn mongo_5_0_without_javascript() {
+    matches_native(Capabilities::for_version((5, 0), false).unwrap());
+}
+
+#[test]
+fn mongo_5_2() {
+    matches_native(Capabilities::for_version((5, 2), true).unwrap());
+}
+
+#[test]
+fn everything() {
+    matches_native(Capabilities::ALL);
+}
+
+#[test]
+fn old_servers_are_rejected() {
+    let error = Capabilities::for_version((4, 2), true).unwrap_err();
+    assert!(error.to_string().contains("too old"), "{}", error);
+}
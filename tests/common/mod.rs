@@ -0,0 +1,15 @@
+//! Helpers shared by the integration tests, each of which uses only some.
+#![allow(dead_code)]
+
+use std::fs;
+
+/// The example input in `tests/examples/{name}.txt`.
+pub fn named_example(name: &str) -> String {
+    let path = format!("{}/tests/examples/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("couldn't read {}: {}", path, e))
+}
+
+/// The puzzle's example input for `day`.
+pub fn example(day: u8) -> String {
+    named_example(&format!("day{}", day))
+}
@@ -0,0 +1,136 @@
+use aoc_2022::mongo::{
+    self, day1,
+    elves::{self, Elf, Query},
+    Capabilities,
+};
+use mongodb::bson::{self, doc, Document};
+
+mod common;
+
+fn example() -> Vec<Document> {
+    day1::parse(common::example(1).as_bytes())
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(line, mut document)| {
+            document.insert("line", line as i64);
+            document
+        })
+        .collect()
+}
+
+fn report(capabilities: Capabilities, input_hash: &str) -> Vec<Document> {
+    let pipeline = day1::report_pipeline(&capabilities, input_hash);
+    mongo::evaluator()
+        .with_capabilities(capabilities)
+        .aggregate(example(), &Vec::from(pipeline))
+        .unwrap()
+}
+
+fn summary(elves: &[Elf]) -> Vec<(i64, i64, i64, i64, f64)> {
+    let mut summary: Vec<_> = elves
+        .iter()
+        .map(|e| {
+            (
+                e.elf.0,
+                e.items.0,
+                e.total_calories.0,
+                e.rank.0,
+                e.percentile,
+            )
+        })
+        .collect();
+    summary.sort_by_key(|e| e.0);
+    summary
+}
+
+fn elves(documents: Vec<Document>) -> Vec<Elf> {
+    documents
+        .into_iter()
+        .map(|document| bson::from_document(document).unwrap())
+        .collect()
+}
+
+#[test]
+fn report_ranks_every_elf() {
+    let variants = [
+        Capabilities::ALL,
+        Capabilities::for_version((4, 4), false).unwrap(),
+    ];
+    for capabilities in variants {
+        let documents = report(capabilities, "abc");
+        assert_eq!(
+            documents[0]
+                .get_document("_id")
+                .unwrap()
+                .get_str("inputHash"),
+            Ok("abc"),
+            "{:?}",
+            capabilities
+        );
+        assert_eq!(
+            summary(&elves(documents)),
+            vec![
+                (1, 3, 6000, 4, 40.0),
+                (2, 1, 4000, 5, 20.0),
+                (3, 2, 11000, 2, 80.0),
+                (4, 3, 24000, 1, 100.0),
+                (5, 1, 10000, 3, 60.0),
+            ],
+            "{:?}",
+            capabilities
+        );
+    }
+}
+
+#[test]
+fn queries_filter_one_inputs_report() {
+    let mut collection = report(Capabilities::ALL, "abc");
+    collection.extend(report(Capabilities::ALL, "def"));
+    let query = |query: Query| {
+        let pipeline = elves::query_pipeline("abc", &query);
+        let results = mongo::evaluator()
+            .aggregate(collection.clone(), &Vec::from(pipeline))
+            .unwrap();
+        elves(results).iter().map(|e| e.elf.0).collect::<Vec<_>>()
+    };
+    assert_eq!(query(Query::default()), vec![4, 3, 5, 1, 2]);
+    assert_eq!(
+        query(Query {
+            more_than: Some(10000),
+            ..Default::default()
+        }),
+        vec![4, 3]
+    );
+    assert_eq!(
+        query(Query {
+            top: Some(3),
+            more_than: Some(5000),
+        }),
+        vec![4, 3, 5]
+    );
+}
+
+#[test]
+fn ties_share_a_rank() {
+    let documents = ["1", "", "3", "", "1"]
+        .iter()
+        .enumerate()
+        .map(|(line, calories)| match calories.parse::<i32>() {
+            Ok(n) => doc! { "line": line as i64, "calories": n },
+            Err(_) => doc! { "line": line as i64 },
+        })
+        .collect::<Vec<_>>();
+    for capabilities in [
+        Capabilities::ALL,
+        Capabilities::for_version((4, 4), false).unwrap(),
+    ] {
+        let pipeline = day1::report_pipeline(&capabilities, "abc");
+        let results = mongo::evaluator()
+            .with_capabilities(capabilities)
+            .aggregate(documents.clone(), &Vec::from(pipeline))
+            .unwrap();
+        let ranks: Vec<_> = summary(&elves(results)).iter().map(|e| e.3).collect();
+        assert_eq!(ranks, vec![2, 1, 2], "{:?}", capabilities);
+    }
+}
@@ -0,0 +1,365 @@
+use aoc_2022::mongo::eval::Evaluator;
+use mongodb::bson::{doc, Bson, Document};
+
+fn run(documents: Vec<Document>, pipeline: Vec<Document>) -> anyhow::Result<Vec<Document>> {
+    Evaluator::new().aggregate(documents, &pipeline)
+}
+
+#[test]
+fn assigns_ids_in_insertion_order() {
+    let results = run(vec![doc! { "a": 1 }, doc! { "a": 2 }], vec![]).unwrap();
+    assert_eq!(
+        results,
+        vec![doc! { "_id": 0_i64, "a": 1 }, doc! { "_id": 1_i64, "a": 2 }]
+    );
+}
+
+#[test]
+fn running_window_sum() {
+    let results = run(
+        vec![doc! { "n": 1 }, doc! { "n": 2 }, doc! { "n": 3 }],
+        vec![
+            doc! {
+                "$setWindowFields": {
+                    "sortBy": { "_id": 1 },
+                    "output": {
+                        "total": {
+                            "$sum": "$n",
+                            "window": { "documents": ["unbounded", "current"] },
+                        },
+                        "before": {
+                            "$count": {},
+                            "window": { "documents": ["unbounded", -1] },
+                        },
+                    },
+                },
+            },
+            doc! { "$project": { "_id": 0, "total": 1, "before": 1 } },
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "total": 1, "before": 0 },
+            doc! { "total": 3, "before": 1 },
+            doc! { "total": 6, "before": 2 },
+        ]
+    );
+}
+
+#[test]
+fn missing_sorts_before_null() {
+    let results = run(
+        vec![doc! {}, doc! { "n": Bson::Null }, doc! { "n": 1 }],
+        vec![doc! {
+            "$project": { "_id": 0, "lt": { "$lt": ["$n", Bson::Null] } },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "lt": true },
+            doc! { "lt": false },
+            doc! { "lt": false }
+        ]
+    );
+}
+
+#[test]
+fn group_top_n_and_push() {
+    let results = run(
+        vec![doc! { "n": 5 }, doc! { "n": 9 }, doc! { "n": 7 }],
+        vec![doc! {
+            "$group": {
+                "_id": Bson::Null,
+                "top": { "$topN": { "output": "$n", "sortBy": { "n": -1 }, "n": 2 } },
+                "all": { "$push": "$n" },
+                "sum": { "$sum": "$n" },
+            },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![doc! { "_id": Bson::Null, "top": [9, 7], "all": [5, 9, 7], "sum": 21 }]
+    );
+}
+
+#[test]
+fn string_and_array_expressions() {
+    let results = run(
+        vec![doc! { "s": "abcd-ef" }],
+        vec![doc! {
+            "$project": {
+                "_id": 0,
+                "parts": { "$split": ["$s", "-"] },
+                "half": { "$substrBytes": ["$s", 0, { "$divide": [{ "$strLenBytes": "$s" }, 2] }] },
+                "chars": {
+                    "$map": {
+                        "input": { "$range": [0, 3] },
+                        "in": { "$substrBytes": ["$s", "$$this", 1] },
+                    },
+                },
+                "common": { "$setIntersection": [["a", "b", "a"], ["b", "a", "c"]] },
+                "n": { "$toInt": { "$arrayElemAt": [{ "$split": ["12-34", "-"] }, -1] } },
+            },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![doc! {
+            "parts": ["abcd", "ef"],
+            "half": "abc",
+            "chars": ["a", "b", "c"],
+            "common": ["a", "b"],
+            "n": 34,
+        }]
+    );
+}
+
+#[test]
+fn functions_use_registered_rust_equivalents() {
+    let body = "function(x) { return x * 2 }";
+    let pipeline = vec![doc! {
+        "$project": {
+            "_id": 0,
+            "doubled": { "$function": { "body": body, "args": ["$n"], "lang": "js" } },
+        },
+    }];
+    let evaluator = Evaluator::new().with_function(body, |args| match args {
+        [Bson::Int32(n)] => Ok(Bson::Int32(n * 2)),
+        _ => anyhow::bail!("expected an int"),
+    });
+    let results = evaluator
+        .aggregate(vec![doc! { "n": 21 }], &pipeline)
+        .unwrap();
+    assert_eq!(results, vec![doc! { "doubled": 42 }]);
+
+    let error = run(vec![doc! { "n": 21 }], pipeline).unwrap_err();
+    assert!(
+        error.to_string().contains("unsupported $function"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn reports_unsupported_stages_and_operators() {
+    let error = run(vec![], vec![doc! { "$lookup": {} }]).unwrap_err();
+    assert_eq!(error.to_string(), "unsupported stage $lookup");
+
+    let error = run(
+        vec![doc! {}],
+        vec![doc! { "$project": { "x": { "$toUpper": "a" } } }],
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unsupported expression operator $toUpper"
+    );
+
+    let error = run(vec![doc! {}], vec![doc! { "$match": { "a": 1 } }]).unwrap_err();
+    assert!(
+        error.to_string().starts_with("unsupported $match query"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn overflowing_integers_become_doubles() {
+    let max = i64::MAX as f64;
+    let results = run(
+        vec![doc! { "n": i64::MAX }, doc! { "n": 1_i64 }],
+        vec![doc! { "$group": { "_id": Bson::Null, "sum": { "$sum": "$n" } } }],
+    )
+    .unwrap();
+    assert_eq!(results, vec![doc! { "_id": Bson::Null, "sum": max + 1.0 }]);
+
+    let results = run(
+        vec![doc! { "n": i64::MAX }],
+        vec![doc! {
+            "$project": {
+                "_id": 0,
+                "sum": { "$add": ["$n", 1] },
+                "difference": { "$subtract": [-2, "$n"] },
+            },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![doc! { "sum": max + 1.0, "difference": -2.0 - max }]
+    );
+}
+
+#[test]
+fn mod_of_the_smallest_integer_by_minus_one_becomes_a_double() {
+    let results = run(
+        vec![doc! { "n": i64::MIN }],
+        vec![doc! { "$project": { "_id": 0, "r": { "$mod": ["$n", -1] } } }],
+    )
+    .unwrap();
+    assert_eq!(results, vec![doc! { "r": 0.0 }]);
+}
+
+#[test]
+fn mod_by_zero_is_an_error() {
+    for divisor in [Bson::Int32(0), Bson::Double(0.0)] {
+        let error = run(
+            vec![doc! { "n": 7 }],
+            vec![doc! { "$project": { "r": { "$mod": ["$n", divisor.clone()] } } }],
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "can't $mod by zero", "{}", divisor);
+    }
+}
+
+#[test]
+fn graph_lookup_follows_parents() {
+    let results = run(
+        vec![
+            doc! { "path": "/", "parent": Bson::Null },
+            doc! { "path": "/a/", "parent": "/" },
+            doc! { "path": "/a/b", "parent": "/a/", "size": 5 },
+            doc! { "path": "/c", "parent": "/", "size": 7 },
+        ],
+        vec![
+            doc! {
+                "$graphLookup": {
+                    "from": "$input",
+                    "startWith": "$path",
+                    "connectFromField": "path",
+                    "connectToField": "parent",
+                    "as": "descendants",
+                },
+            },
+            doc! { "$project": { "_id": 0, "path": 1, "total": { "$sum": { "$map": { "input": "$descendants", "in": "$$this.size" } } } } },
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "path": "/", "total": 12 },
+            doc! { "path": "/a/", "total": 5 },
+            doc! { "path": "/a/b", "total": 0 },
+            doc! { "path": "/c", "total": 0 },
+        ]
+    );
+
+    let error = run(
+        vec![],
+        vec![doc! {
+            "$graphLookup": {
+                "from": "other",
+                "startWith": "$path",
+                "connectFromField": "path",
+                "connectToField": "parent",
+                "as": "descendants",
+            },
+        }],
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("only the input collection"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn min_and_max_accumulators() {
+    let results = run(
+        vec![doc! { "n": 3 }, doc! { "n": 1 }, doc! { "m": 0 }, doc! { "n": 2 }],
+        vec![doc! { "$group": { "_id": Bson::Null, "lo": { "$min": "$n" }, "hi": { "$max": "$n" } } }],
+    )
+    .unwrap();
+    assert_eq!(results, vec![doc! { "_id": Bson::Null, "lo": 1, "hi": 3 }]);
+}
+
+#[test]
+fn sliding_window_sets() {
+    let results = run(
+        vec![doc! { "s": "abba" }],
+        vec![
+            doc! { "$project": { "c": { "$map": { "input": { "$range": [0, 4] }, "in": { "$substrBytes": ["$s", "$$this", 1] } } } } },
+            doc! { "$unwind": { "path": "$c", "includeArrayIndex": "i" } },
+            doc! {
+                "$setWindowFields": {
+                    "sortBy": { "i": 1 },
+                    "output": {
+                        "seen": { "$addToSet": "$c", "window": { "documents": [-1, "current"] } },
+                        "last": { "$push": "$c", "window": { "documents": [-1, "current"] } },
+                    },
+                },
+            },
+            doc! { "$project": { "_id": 0, "i": 1, "seen": 1, "last": 1 } },
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            doc! { "i": 0_i64, "seen": ["a"], "last": ["a"] },
+            doc! { "i": 1_i64, "seen": ["a", "b"], "last": ["a", "b"] },
+            doc! { "i": 2_i64, "seen": ["b"], "last": ["b", "b"] },
+            doc! { "i": 3_i64, "seen": ["b", "a"], "last": ["b", "a"] },
+        ]
+    );
+}
+
+#[test]
+fn slices_sets_and_concatenation() {
+    let results = run(
+        vec![doc! { "a": [1, 2, 2, 3] }],
+        vec![doc! {
+            "$project": {
+                "_id": 0,
+                "tail": { "$slice": ["$a", 1, 2] },
+                "head": { "$slice": ["$a", 2] },
+                "distinct": { "$size": { "$setUnion": ["$a", [4]] } },
+                "product": { "$multiply": [{ "$size": "$a" }, 3] },
+                "text": { "$concat": ["a", "-", "b"] },
+            },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![doc! { "tail": [2, 2], "head": [1, 2], "distinct": 4, "product": 12, "text": "a-b" }]
+    );
+}
+
+#[test]
+fn overflowing_products_become_doubles() {
+    let results = run(
+        vec![doc! { "n": i64::MAX }],
+        vec![doc! { "$project": { "_id": 0, "product": { "$multiply": ["$n", 2] } } }],
+    )
+    .unwrap();
+    assert_eq!(results, vec![doc! { "product": i64::MAX as f64 * 2.0 }]);
+}
+
+#[test]
+fn code_points_and_literals() {
+    let results = run(
+        vec![doc! { "s": "αβγ" }],
+        vec![doc! {
+            "$project": {
+                "_id": 0,
+                "length": { "$strLenCP": "$s" },
+                "middle": { "$substrCP": ["$s", 1, 1] },
+                "dollar": { "$literal": "$s" },
+            },
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        results,
+        vec![doc! { "length": 3, "middle": "β", "dollar": "$s" }]
+    );
+}
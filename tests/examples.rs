@@ -0,0 +1,196 @@
+use aoc_2022::*;
+use std::fs;
+
+mod common;
+
+fn check(solver: fn(&str) -> anyhow::Result<Answer>, name: &str, expected: &str) {
+    assert_eq!(
+        solver(&common::named_example(name)).unwrap().to_string(),
+        expected
+    );
+}
+
+#[test]
+fn day1() {
+    check(day1::part1, "day1", "24000");
+    check(day1::part2, "day1", "45000");
+}
+
+#[test]
+fn day2() {
+    check(
+        |input| day2::part1(input, &day2::Rules::default()),
+        "day2",
+        "15",
+    );
+    check(
+        |input| day2::part2(input, &day2::Rules::default()),
+        "day2",
+        "12",
+    );
+}
+
+#[test]
+fn day3() {
+    check(
+        |input| day3::part1(input, &day3::Config::default()),
+        "day3",
+        "157",
+    );
+    check(
+        |input| day3::part2(input, &day3::Config::default()),
+        "day3",
+        "70",
+    );
+}
+
+#[test]
+fn day4() {
+    check(day4::part1, "day4", "2");
+    check(day4::part2, "day4", "4");
+}
+
+#[test]
+fn day5() {
+    check(day5::part1, "day5", "CMZ");
+    check(day5::part2, "day5", "MCD");
+}
+
+#[test]
+fn day6() {
+    check(day6::part1, "day6", "7");
+    check(day6::part2, "day6", "19");
+}
+
+#[test]
+fn day7() {
+    check(day7::part1, "day7", "95437");
+    check(day7::part2, "day7", "24933642");
+}
+
+#[test]
+fn day8() {
+    check(day8::part1, "day8", "21");
+    check(day8::part2, "day8", "8");
+}
+
+#[test]
+fn day9() {
+    check(day9::part1, "day9", "13");
+    check(day9::part2, "day9", "1");
+    check(day9::part2, "day9-larger", "36");
+}
+
+#[test]
+fn day10() {
+    check(day10::part1, "day10", "13140");
+    check(
+        day10::part2,
+        "day10",
+        "##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....",
+    );
+}
+
+#[test]
+fn day11() {
+    check(day11::part1, "day11", "10605");
+    check(day11::part2, "day11", "2713310158");
+}
+
+#[test]
+fn day12() {
+    check(day12::part1, "day12", "31");
+    check(day12::part2, "day12", "29");
+}
+
+#[test]
+fn day13() {
+    check(day13::part1, "day13", "13");
+    check(day13::part2, "day13", "140");
+}
+
+#[test]
+fn day14() {
+    check(day14::part1, "day14", "24");
+    check(day14::part2, "day14", "93");
+}
+
+#[test]
+fn day15() {
+    let input = common::example(15);
+    assert_eq!(
+        day15::excluded_in_row(&input, 10).unwrap().to_string(),
+        "26"
+    );
+    assert_eq!(
+        day15::tuning_frequency(&input, 20).unwrap().to_string(),
+        "56000011"
+    );
+}
+
+#[test]
+fn day16() {
+    check(day16::part1, "day16", "1651");
+    check(day16::part2, "day16", "1707");
+}
+
+#[test]
+fn day17() {
+    check(day17::part1, "day17", "3068");
+    check(day17::part2, "day17", "1514285714288");
+}
+
+#[test]
+fn day18() {
+    check(day18::part1, "day18", "64");
+    check(day18::part2, "day18", "58");
+}
+
+#[test]
+fn day19() {
+    check(day19::part1, "day19", "33");
+    check(day19::part2, "day19", "3472");
+}
+
+#[test]
+fn day20() {
+    check(day20::part1, "day20", "3");
+    check(day20::part2, "day20", "1623178306");
+}
+
+#[test]
+fn day21() {
+    check(day21::part1, "day21", "152");
+    check(day21::part2, "day21", "301");
+}
+
+#[test]
+fn day22() {
+    check(day22::part1, "day22", "6032");
+    // The cube folding is hard-coded for the shape of the real input, which
+    // differs from the example's, so part 2 is checked against the real input.
+    let input = fs::read_to_string(format!("{}/input22.txt", env!("CARGO_MANIFEST_DIR"))).unwrap();
+    assert_eq!(day22::part2(&input).unwrap().to_string(), "127012");
+}
+
+#[test]
+fn day23() {
+    check(day23::part1, "day23", "110");
+    check(day23::part2, "day23", "20");
+}
+
+#[test]
+fn day24() {
+    check(day24::part1, "day24", "18");
+    check(day24::part2, "day24", "54");
+}
+
+#[test]
+fn day25() {
+    check(day25::part1, "day25", "2=-1=0");
+}
@@ -0,0 +1,112 @@
+use aoc_2022::mongo::{
+    self,
+    explain::{self, Stage},
+    pipeline::*,
+};
+use mongodb::bson::{doc, Bson};
+use std::time::Duration;
+
+#[test]
+fn server_stages() {
+    let explained = doc! {
+        "explainVersion": "1",
+        "stages": [
+            {
+                "$cursor": {
+                    "queryPlanner": {},
+                    "executionStats": {
+                        "nReturned": 300,
+                        "executionTimeMillis": 3,
+                        "totalDocsExamined": 300,
+                    },
+                },
+                "nReturned": 300_i64,
+                "executionTimeMillisEstimate": 2_i64,
+            },
+            {
+                "$setWindowFields": {},
+                "nReturned": 300_i64,
+                "executionTimeMillisEstimate": 5_i64,
+                "usedDisk": false,
+            },
+            {
+                "$group": {},
+                "nReturned": 100_i64,
+                "executionTimeMillisEstimate": 12_i64,
+                "usedDisk": true,
+                "spills": 2_i64,
+            },
+        ],
+        "ok": 1.0,
+    };
+    let stage = |name: &str, docs_in, docs_out, millis, spilled| Stage {
+        name: name.to_string(),
+        docs_in: Some(docs_in),
+        docs_out: Some(docs_out),
+        elapsed: Some(Duration::from_millis(millis)),
+        spilled,
+    };
+    assert_eq!(
+        explain::stages(&explained),
+        vec![
+            stage("$cursor", 300, 300, 2, false),
+            stage("$setWindowFields", 300, 300, 3, false),
+            stage("$group", 300, 100, 7, true),
+        ]
+    );
+}
+
+#[test]
+fn pushed_down_query() {
+    let explained = doc! {
+        "queryPlanner": {},
+        "executionStats": {
+            "nReturned": 1,
+            "executionTimeMillis": 4,
+            "totalDocsExamined": 2237,
+        },
+    };
+    assert_eq!(
+        explain::stages(&explained),
+        vec![Stage {
+            name: "query".to_string(),
+            docs_in: Some(2237),
+            docs_out: Some(1),
+            elapsed: Some(Duration::from_millis(4)),
+            spilled: false,
+        }]
+    );
+}
+
+#[test]
+fn offline_stages() {
+    let documents = (1..=6).map(|n| doc! { "n": n }).collect();
+    let pipeline: Vec<_> = Pipeline::new()
+        .match_expr(gte(field("n"), 3))
+        .group(Group::by(Bson::Null).sum("total", field("n")))
+        .into();
+    let (results, stages) = mongo::evaluator().explain(documents, &pipeline).unwrap();
+    assert_eq!(results, vec![doc! { "_id": Bson::Null, "total": 18 }]);
+    let counts: Vec<_> = stages
+        .iter()
+        .map(|s| (s.name.as_str(), s.docs_in, s.docs_out, s.spilled))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("$match", Some(6), Some(4), false),
+            ("$group", Some(4), Some(1), false),
+        ]
+    );
+}
+
+#[test]
+fn writing_pipelines_are_detected() {
+    let reading: Vec<_> = Pipeline::new().limit(1).into();
+    assert!(!explain::writes(&reading));
+    assert!(explain::writes(&[
+        doc! { "$limit": 1 },
+        doc! { "$merge": { "into": "elves" } },
+    ]));
+    assert!(explain::writes(&[doc! { "$out": "elves" }]));
+}
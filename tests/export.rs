@@ -0,0 +1,98 @@
+use aoc_2022::{
+    mongo::{
+        self,
+        export::{self, Export},
+        Capabilities,
+    },
+    solver, Answer, Backend, Context,
+};
+use mongodb::bson::{Bson, Document};
+
+mod common;
+
+/// The values passed to `EJSON.deserialize` in `script`, in order.
+fn deserialized(script: &str) -> Vec<Bson> {
+    script
+        .split("EJSON.deserialize(")
+        .skip(1)
+        .map(|rest| {
+            let end = rest.find(");\n").unwrap();
+            Bson::try_from(serde_json::from_str::<serde_json::Value>(&rest[..end]).unwrap())
+                .unwrap()
+        })
+        .collect()
+}
+
+fn documents(value: &Bson) -> Vec<Document> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d.as_document().unwrap().clone())
+        .collect()
+}
+
+/// Running the script's pipelines over its documents gives the same answers
+/// as the native solvers.
+#[test]
+fn scripts_reproduce_answers() {
+    let variants = [
+        Capabilities::ALL,
+        Capabilities::for_version((4, 4), false).unwrap(),
+    ];
+    for capabilities in variants {
+        for day in export::days() {
+            let input = common::example(day);
+            // The evaluator only knows the input collection by its placeholder.
+            let script = Export::new(day, &input, &Context::default(), &capabilities)
+                .unwrap()
+                .script(mongo::INPUT)
+                .unwrap();
+            let values = deserialized(&script);
+            assert_eq!(values.len(), 3, "day {}", day);
+            for (part, pipeline) in (1..).zip(&values[1..]) {
+                let native = solver(day, part, Backend::Native).unwrap();
+                let results = mongo::evaluator()
+                    .with_capabilities(capabilities)
+                    .aggregate(documents(&values[0]), &documents(pipeline))
+                    .unwrap();
+                let expected = native(&input, &Context::default()).unwrap();
+                let value = results[0]
+                    .iter()
+                    .find(|(key, _)| *key != "_id")
+                    .map(|(_, value)| value.clone())
+                    .unwrap();
+                let actual = match value {
+                    Bson::Int32(n) => Answer::Num(n.into()),
+                    Bson::Int64(n) => Answer::Num(n),
+                    Bson::Double(n) => Answer::Num(n as i64),
+                    Bson::String(s) => Answer::Text(s),
+                    value => panic!("unexpected result {}", value),
+                };
+                assert_eq!(actual, expected, "day {} with {:?}", day, capabilities);
+            }
+        }
+    }
+}
+
+#[test]
+fn scripts_say_how_to_run_them() {
+    let input = common::example(1);
+    let script = Export::new(1, &input, &Context::default(), &Capabilities::ALL)
+        .unwrap()
+        .script("input1")
+        .unwrap();
+    assert!(script.contains("mongosh <uri>/aoc2022 day1.js"));
+}
+
+#[test]
+fn scripts_name_the_collection() {
+    let input = common::example(7);
+    let script = Export::new(7, &input, &Context::default(), &Capabilities::ALL)
+        .unwrap()
+        .script("input7")
+        .unwrap();
+    assert!(script.contains(r#"db.getCollection("input7")"#));
+    assert!(script.contains(r#""from": "input7""#));
+    assert!(!script.contains(mongo::INPUT));
+}
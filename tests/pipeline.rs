@@ -0,0 +1,57 @@
+use aoc_2022::mongo::pipeline::*;
+use mongodb::bson::{doc, Bson, Document};
+
+#[test]
+fn helpers_compile_to_operators() {
+    assert_eq!(
+        Bson::from(chars(field("s"))),
+        Bson::Document(doc! {
+            "$map": {
+                "input": { "$range": [0, { "$strLenBytes": "$s" }] },
+                "in": { "$substrBytes": ["$s", "$$this", 1] },
+            },
+        })
+    );
+    assert_eq!(
+        Bson::from(split_at(field("pairs"), ",", 1)),
+        Bson::Document(doc! { "$arrayElemAt": [{ "$split": ["$pairs", ","] }, 1] })
+    );
+}
+
+#[test]
+fn stages_compile_in_order() {
+    let pipeline: Vec<Document> = Pipeline::new()
+        .set_window_fields(SetWindowFields::new().sort_by(&[("_id", 1)]).output(
+            "i",
+            Window::count().documents(Bound::Unbounded, Bound::Offset(-1)),
+        ))
+        .match_expr(field("i"))
+        .group(Group::by(Bson::Null).top_n("top", field("i"), &[("i", -1)], 2))
+        .project([("top", sum(field("top")))])
+        .merge("out")
+        .into();
+    assert_eq!(
+        pipeline,
+        vec![
+            doc! {
+                "$setWindowFields": {
+                    "sortBy": { "_id": 1 },
+                    "output": {
+                        "i": { "$count": {}, "window": { "documents": ["unbounded", -1] } },
+                    },
+                },
+            },
+            doc! { "$match": { "$expr": "$i" } },
+            doc! {
+                "$group": {
+                    "_id": Bson::Null,
+                    "top": { "$topN": { "output": "$i", "sortBy": { "i": -1 }, "n": 2 } },
+                },
+            },
+            doc! { "$project": { "top": { "$sum": "$top" } } },
+            doc! {
+                "$merge": { "into": "out", "whenMatched": "replace", "whenNotMatched": "insert" },
+            },
+        ]
+    );
+}
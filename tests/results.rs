@@ -0,0 +1,69 @@
+use aoc_2022::{mongo::results::Record, Answer, Backend};
+use mongodb::bson::DateTime;
+use std::time::Duration;
+
+#[test]
+fn document_round_trip() {
+    let records = [
+        Record {
+            day: 1,
+            part: 2,
+            result: Ok(Answer::Num(213159)),
+            backend: Backend::MongoOffline,
+            elapsed: Duration::from_millis(20),
+            input_hash: Some("abc".to_string()),
+            commit: Some("def".to_string()),
+            timestamp: DateTime::from_millis(1_670_000_000_000),
+        },
+        Record {
+            day: 10,
+            part: 2,
+            result: Ok(Answer::Text("##..\n#..#".to_string())),
+            backend: Backend::Native,
+            elapsed: Duration::from_micros(5),
+            input_hash: None,
+            commit: None,
+            timestamp: DateTime::from_millis(1_670_000_000_000),
+        },
+        Record {
+            day: 16,
+            part: 1,
+            result: Err("no path".to_string()),
+            backend: Backend::Native,
+            elapsed: Duration::ZERO,
+            input_hash: None,
+            commit: None,
+            timestamp: DateTime::from_millis(0),
+        },
+    ];
+    for record in records {
+        assert_eq!(
+            Record::from_document(&record.to_document()).unwrap(),
+            record
+        );
+    }
+}
+
+#[test]
+fn parts_outside_the_puzzle_are_rejected() {
+    let record = Record {
+        day: 1,
+        part: 1,
+        result: Ok(Answer::Num(1)),
+        backend: Backend::Native,
+        elapsed: Duration::ZERO,
+        input_hash: None,
+        commit: None,
+        timestamp: DateTime::from_millis(0),
+    };
+    for part in [0, 3] {
+        let mut document = record.to_document();
+        document.insert("part", part);
+        let error = Record::from_document(&document).unwrap_err();
+        assert!(
+            error.to_string().contains(&format!("no part {}", part)),
+            "{}",
+            error
+        );
+    }
+}
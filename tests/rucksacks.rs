@@ -0,0 +1,75 @@
+use aoc_2022::{day3::Config, mongo::Capabilities, solver, Answer, Backend, Context};
+
+const BACKENDS: [Backend; 3] = [Backend::Native, Backend::MongoOffline, Backend::Sqlite];
+
+/// What part `part` of day 3 gives for `input` on `backend` under `context`.
+fn solve(part: u8, backend: Backend, input: &str, context: &Context) -> anyhow::Result<Answer> {
+    solver(3, part, backend).unwrap()(input, context)
+}
+
+#[test]
+fn every_backend_diagnoses_alike() {
+    let input = "abcab\nabcd\nabab\na1b1\n";
+    let part1 = "couldn't score the input:\n\
+                 line 1: an odd number of items\n\
+                 line 2: no common item\n\
+                 line 3: more than one common item\n\
+                 line 4: common item 1 is not in the alphabet";
+    let part2 = "couldn't score the input:\n\
+                 lines 1-3: more than one common item\n\
+                 line 4: fewer than 3 rucksacks in the group";
+    for emulated in [
+        Capabilities::ALL,
+        Capabilities::for_version((4, 4), false).unwrap(),
+    ] {
+        let context = Context {
+            emulated,
+            ..Context::default()
+        };
+        for backend in BACKENDS {
+            assert_eq!(
+                solve(1, backend, input, &context).unwrap_err().to_string(),
+                part1,
+                "{:?}",
+                backend
+            );
+            assert_eq!(
+                solve(2, backend, input, &context).unwrap_err().to_string(),
+                part2,
+                "{:?}",
+                backend
+            );
+        }
+    }
+}
+
+#[test]
+fn every_backend_groups_and_prioritizes_by_the_config() {
+    let context = Context {
+        rucksacks: Config::new(2, "αβγδε".to_string()).unwrap(),
+        ..Context::default()
+    };
+    let input = "αβγα\nδεεγ\n";
+    for backend in BACKENDS {
+        assert_eq!(
+            solve(1, backend, input, &context).unwrap(),
+            Answer::Num(6),
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            solve(2, backend, input, &context).unwrap(),
+            Answer::Num(3),
+            "{:?}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    let error = Config::new(2, "abca".to_string()).unwrap_err();
+    assert_eq!(error.to_string(), "a is in the alphabet twice");
+    let error = Config::new(0, "abc".to_string()).unwrap_err();
+    assert_eq!(error.to_string(), "groups need at least one rucksack");
+}
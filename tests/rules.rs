@@ -0,0 +1,118 @@
+use aoc_2022::{day2::Rules, solver, Answer, Backend, Context};
+use std::path::Path;
+
+mod common;
+
+const BACKENDS: [Backend; 3] = [Backend::Native, Backend::MongoOffline, Backend::Sqlite];
+
+fn load(name: &str) -> Rules {
+    Rules::load(
+        &Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("rules")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn rpsls() -> Context {
+    Context {
+        rules: load("rock-paper-scissors-lizard-spock.toml"),
+        ..Context::default()
+    }
+}
+
+/// What part `part` of day 2 gives for `input` on `backend` under `context`.
+fn play(part: u8, backend: Backend, input: &str, context: &Context) -> anyhow::Result<Answer> {
+    solver(2, part, backend).unwrap()(input, context)
+}
+
+fn error(part: u8, backend: Backend, input: &str) -> String {
+    play(part, backend, input, &rpsls())
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn the_rules_file_matches_the_built_in_rules() {
+    assert_eq!(
+        load("rock-paper-scissors.toml"),
+        Rules::rock_paper_scissors()
+    );
+    assert_eq!(Context::default().rules, Rules::rock_paper_scissors());
+}
+
+#[test]
+fn every_backend_plays_by_the_rules() {
+    let input = common::named_example("day2-rpsls");
+    for backend in BACKENDS {
+        assert_eq!(
+            play(1, backend, &input, &rpsls()).unwrap(),
+            Answer::Num(27),
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            play(2, backend, &input, &rpsls()).unwrap(),
+            Answer::Num(39),
+            "{:?}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn unknown_strategies_are_rejected() {
+    // W is a shape but not an outcome.
+    for backend in BACKENDS {
+        assert_eq!(
+            error(1, backend, "A W\nB Q\n"),
+            "unknown strategy: Q",
+            "{:?}",
+            backend
+        );
+        assert_eq!(
+            error(2, backend, "A W\n"),
+            "unknown strategy: W",
+            "{:?}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn unknown_opponent_moves_are_reported_first() {
+    for backend in BACKENDS {
+        assert_eq!(
+            error(1, backend, "A Q\nF X\n"),
+            "unknown opponent move: F",
+            "{:?}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn unbalanced_games_are_rejected() {
+    let rules = include_str!("../rules/rock-paper-scissors.toml");
+    let error = Rules::parse(&rules.replace(
+        "beats = [\"scissors\"]",
+        "beats = [\"scissors\", \"paper\"]",
+    ))
+    .unwrap_err();
+    assert!(error.to_string().contains("instead of half"), "{}", error);
+
+    let error =
+        Rules::parse(&rules.replace("beats = [\"paper\"]", "beats = [\"rock\"]")).unwrap_err();
+    assert!(error.to_string().contains("exactly one of"), "{}", error);
+
+    let error =
+        Rules::parse(&rules.replace("beats = [\"paper\"]", "beats = [\"rok\"]")).unwrap_err();
+    assert!(error.to_string().contains("unknown shape rok"), "{}", error);
+
+    let four = format!(
+        "{}\n[[shapes]]\nname = \"well\"\nopponent = \"D\"\nplayer = \"W\"\nscore = 4\nbeats = []\n",
+        rules
+    );
+    let error = Rules::parse(&four).unwrap_err();
+    assert!(error.to_string().contains("odd number"), "{}", error);
+}
@@ -0,0 +1,93 @@
+use aoc_2022::{
+    day2::{self, Mapping, Rules},
+    mongo::{self, Offline},
+    Context,
+};
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn every_mapping_is_scored_best_first() {
+    let analysis = day2::analyze(&common::example(2), &Rules::default()).unwrap();
+    assert_eq!(analysis.opponent, vec![1, 1, 1]);
+    let mapping = |shapes: [usize; 3], total| Mapping {
+        shapes: shapes.to_vec(),
+        total,
+    };
+    assert_eq!(
+        analysis.mappings,
+        vec![
+            mapping([2, 1, 0], 24),
+            mapping([0, 1, 2], 15),
+            mapping([1, 0, 2], 15),
+            mapping([1, 2, 0], 15),
+            mapping([2, 0, 1], 15),
+            mapping([0, 2, 1], 6),
+        ]
+    );
+    assert_eq!(analysis.best(), analysis.mappings.first());
+    assert_eq!(analysis.rounds(), 3);
+}
+
+#[test]
+fn pipelines_match_native() {
+    let input = common::example(2);
+    assert_eq!(
+        mongo::day2::analyze::<Offline>(&input, &Context::default()).unwrap(),
+        day2::analyze(&input, &Rules::default()).unwrap()
+    );
+}
+
+#[test]
+fn pipelines_play_by_the_rules() {
+    let rules = Rules::load(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("rules/rock-paper-scissors-lizard-spock.toml"),
+    )
+    .unwrap();
+    let input = common::named_example("day2-rpsls");
+    let context = Context {
+        rules: rules.clone(),
+        ..Context::default()
+    };
+    assert_eq!(
+        mongo::day2::analyze::<Offline>(&input, &context).unwrap(),
+        day2::analyze(&input, &rules).unwrap()
+    );
+}
+
+#[test]
+fn unknown_symbols_are_rejected() {
+    let context = Context::default();
+    let error = mongo::day2::analyze::<Offline>("D X\n", &context).unwrap_err();
+    assert_eq!(error.to_string(), "unknown opponent move: D");
+    let error = mongo::day2::analyze::<Offline>("A X\nB Q\n", &context).unwrap_err();
+    assert_eq!(error.to_string(), "unknown strategy: Q");
+}
+
+/// A game of `n` shapes in which each beats the next half of the others.
+fn cyclic_rules(n: usize) -> Rules {
+    let mut rules = String::new();
+    for i in 0..n {
+        let beats: Vec<String> = (1..=n / 2)
+            .map(|j| format!("\"s{}\"", (i + j) % n))
+            .collect();
+        rules += &format!(
+            "[[shapes]]\nname = \"s{i}\"\nopponent = \"o{i}\"\nplayer = \"p{i}\"\nscore = {i}\nbeats = [{}]\n",
+            beats.join(", ")
+        );
+    }
+    rules += "[outcomes]\nlose = { player = \"L\", score = 0 }\ndraw = { player = \"D\", score = 3 }\nwin = { player = \"W\", score = 6 }\n";
+    Rules::parse(&rules).unwrap()
+}
+
+#[test]
+fn mappings_are_limited_to_small_games() {
+    assert_eq!(cyclic_rules(5).mappings().unwrap().len(), 120);
+    let error = cyclic_rules(11).mappings().unwrap_err();
+    assert!(
+        error.to_string().contains("more than 9 shapes"),
+        "{}",
+        error
+    );
+}